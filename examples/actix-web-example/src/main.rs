@@ -15,11 +15,13 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use actix_web::{middleware, App, HttpServer, Responder};
-use shenyu_client_rust::actix_web_impl::ShenYuRouter;
+use actix_web::{middleware, web, App, HttpServer, Responder};
+use shenyu_client_rust::actix_web_impl::{metrics_resource, trace_context, ShenYuRouter};
 use shenyu_client_rust::ci::_CI_CTRL_C;
 use shenyu_client_rust::config::ShenYuConfig;
+use shenyu_client_rust::metrics::ClientMetrics;
 use shenyu_client_rust::{register_once, shenyu_router};
+use std::sync::Arc;
 
 async fn health_handler() -> impl Responder {
     "OK"
@@ -40,10 +42,15 @@ async fn main() -> std::io::Result<()> {
     // Initialize tracing
     tracing_subscriber::fmt::init();
 
+    let config = ShenYuConfig::from_yaml_file("config.yml").unwrap();
+    let metrics = Arc::new(ClientMetrics::new());
     HttpServer::new(move || {
         let mut router = ShenYuRouter::new("shenyu_client_app");
-        let mut app = App::new().wrap(middleware::Logger::default());
-        let config = ShenYuConfig::from_yaml_file("config.yml").unwrap();
+        let mut app = App::new()
+            .wrap(middleware::Logger::default())
+            .wrap(middleware::from_fn(trace_context))
+            .service(metrics_resource("/metrics", metrics.clone()));
+        let config = config.clone();
         // fixme the handler method name, should be `actix-web-example::health_handler`
         shenyu_router!(
             router,
@@ -52,7 +59,8 @@ async fn main() -> std::io::Result<()> {
             "/create_user" => post(create_user_handler)
             "/" => get(index)
         );
-        register_once!(config, router, 4000);
+        register_once!(config, router, 4000, metrics.clone());
+        app = app.app_data(web::Data::new(router));
 
         app
     })
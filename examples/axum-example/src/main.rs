@@ -21,7 +21,10 @@ use shenyu_client_rust::axum_impl::ShenYuRouter;
 use shenyu_client_rust::ci::_CI_CTRL_C;
 use shenyu_client_rust::config::ShenYuConfig;
 use shenyu_client_rust::core::ShenyuClient;
+use shenyu_client_rust::metrics::ClientMetrics;
+use shenyu_client_rust::status::STATUS_PATH;
 use shenyu_client_rust::IRouter;
+use std::sync::Arc;
 
 async fn health_handler() -> &'static str {
     "OK"
@@ -38,6 +41,7 @@ async fn main() {
     // Initialize tracing
     tracing_subscriber::fmt::init();
 
+    let metrics = Arc::new(ClientMetrics::new());
     let app = ShenYuRouter::<()>::new("shenyu_client_app")
         .nest("/api", ShenYuRouter::new("api"))
         .route(
@@ -53,12 +57,22 @@ async fn main() {
                 stringify!(create_user_handler)
             ),
             post(create_user_handler),
-        );
+        )
+        .trace_context()
+        .metrics("/metrics", metrics.clone());
     let config = ShenYuConfig::from_yaml_file("config.yml").unwrap();
-    let client = ShenyuClient::new(config, app.app_name(), app.uri_infos(), 3000).unwrap();
+    let client = Arc::new(
+        ShenyuClient::new(config, app.app_name(), app.uri_infos(), 3000)
+            .unwrap()
+            .metrics(metrics),
+    );
 
-    let axum_app: Router = app.into();
-    client.register().expect("TODO: panic message");
+    let axum_app: Router = app.status(STATUS_PATH, client.clone(), None).into();
+    match client.register() {
+        Ok(report) if !report.is_success() => eprintln!("registered partially: {report:?}"),
+        Ok(_) => {}
+        Err(e) => eprintln!("failed to register: {e}"),
+    }
 
     // Start Axum server
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
@@ -67,7 +81,7 @@ async fn main() {
             tokio::signal::ctrl_c()
                 .await
                 .expect("failed to listen for event");
-            client.offline_register();
+            _ = client.offline_register();
         })
         .await
         .unwrap();
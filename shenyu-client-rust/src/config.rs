@@ -15,70 +15,500 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use serde::Deserialize;
+use crate::error::ShenYuError;
+use crate::secret::Secret;
+use schemars::JsonSchema;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_yaml;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
 
-#[allow(missing_docs)]
-#[derive(Debug, Deserialize)]
+mod builder;
+mod env;
+mod format;
+mod java;
+mod profile;
+mod schema;
+mod validate;
+
+pub use self::builder::ShenYuConfigBuilder;
+use self::env::Placeholders;
+pub use self::env::ENV_OVERRIDE_PREFIX;
+pub use self::format::ConfigFormat;
+pub use self::profile::PROFILE_ENV;
+use self::schema::{discovery_type, profiles, register_type, rpc_type, scalar_map};
+pub(crate) use self::validate::check_admin_url;
+pub use self::validate::{ConfigErrors, ConfigIssue, DISCOVERY_TYPES, REGISTER_TYPES, RPC_TYPES};
+
+/// The root of the configuration file.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EnvConfig {
+    /// The shenyu client configuration.
     pub(crate) shenyu: ShenYuConfig,
 }
 
-#[allow(missing_docs)]
-#[derive(Debug, Deserialize)]
+/// The shenyu client configuration.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+#[schemars(transform = profiles)]
 pub struct ShenYuConfig {
+    /// How to reach and authenticate with shenyu admin.
     pub register: RegisterConfig,
+    /// What this service advertises to the gateway.
     pub uri: UriConfig,
+    /// The discovery config registered for the service's selector.
     pub discovery: DiscoveryConfig,
+    /// Independent shenyu admin deployments to register with, replacing
+    /// `register` when set.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<RegisterTarget>,
 }
 
 impl ShenYuConfig {
     /// Load configuration from a YAML file.
-    pub fn from_yaml_file(file_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let current_dir = std::env::current_dir().unwrap();
+    ///
+    /// `${VAR}` and `${VAR:-default}` placeholders in the file's values are
+    /// replaced from the environment, then `SHENYU_<SECTION>_<FIELD>` variables
+    /// override the parsed fields, see [`ShenYuConfig::apply_overrides`].
+    pub fn from_yaml_file(file_path: &str) -> Result<Self, ShenYuError> {
+        let contents = read_file(file_path.as_ref())?;
+        Self::from_str_with_format(&contents, ConfigFormat::Yaml)
+    }
+
+    /// Load configuration from the Java client's `application.yml`, using
+    /// its camelCase layout (`shenyu.register.serverLists`,
+    /// `shenyu.client.http.props.contextPath`...).
+    ///
+    /// The other loaders also accept this layout when they recognize it.
+    pub fn from_java_yaml_file<P: AsRef<Path>>(path: P) -> Result<Self, ShenYuError> {
+        let contents = read_file(path.as_ref())?;
+        let mut value = ConfigFormat::Yaml
+            .parse(&contents)
+            .map_err(|e| ShenYuError::serialization("parse the java client config", e))?;
+        interpolate_env(&mut value, Placeholders::Spring)?;
+        let mut config = serde_yaml::to_string(&value)
+            .and_then(|yaml| java::from_yaml_str(&yaml))
+            .map_err(|e| ShenYuError::serialization("read the java client config", e))?;
+        config.apply_env_overrides()?;
+        Ok(config)
+    }
 
-        let mut file = File::open(current_dir.join(file_path))?;
-        let mut contents = String::new();
-        _ = file.read_to_string(&mut contents)?;
-        let config: EnvConfig = serde_yaml::from_str(&contents)?;
-        Ok(config.shenyu)
+    /// Load configuration from a file, in the format given by its extension
+    /// or, failing that, detected from its contents.
+    ///
+    /// Relative paths are resolved against the current directory.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, ShenYuError> {
+        let path = path.as_ref();
+        let contents = read_file(path)?;
+        let format =
+            ConfigFormat::from_path(path).unwrap_or_else(|| ConfigFormat::detect(&contents));
+        Self::from_str_with_format(&contents, format)
     }
+
+    /// Load configuration from `contents` in the given format.
+    ///
+    /// Interpolation and environment overrides apply as in
+    /// [`ShenYuConfig::from_yaml_file`]. If the config has a
+    /// `shenyu.profiles` section, the profile named by the `SHENYU_PROFILE`
+    /// environment variable, by `SHENYU_URI_ENVIRONMENT` or else by
+    /// `uri.environment` is merged over the base config first, so only the
+    /// selected profile is interpolated.
+    pub fn from_str_with_format(contents: &str, format: ConfigFormat) -> Result<Self, ShenYuError> {
+        let mut value = format
+            .parse(contents)
+            .map_err(|e| ShenYuError::serialization(format!("parse the {format:?} config"), e))?;
+        let syntax = if java::is_java_layout(&value) {
+            Placeholders::Spring
+        } else {
+            Placeholders::Shell
+        };
+        let lookup = |name: &str| std::env::var(name).ok();
+        profile::apply_profile(
+            &mut value,
+            profile::selected(&lookup).as_deref(),
+            &lookup,
+            syntax,
+        )
+        .map_err(|e| ShenYuError::config("apply the config profile", e))?;
+        interpolate_env(&mut value, syntax)?;
+        // Going through YAML text gives every format the same lenient
+        // handling of scalars, e.g. `password = 123456` into a string.
+        let parsed = serde_yaml::to_string(&value).and_then(|yaml| {
+            if java::is_java_layout(&value) {
+                java::from_yaml_str(&yaml)
+            } else {
+                serde_yaml::from_str::<EnvConfig>(&yaml).map(|env| env.shenyu)
+            }
+        });
+        let mut config =
+            parsed.map_err(|e| ShenYuError::serialization("read the shenyu config", e))?;
+        config.apply_env_overrides()?;
+        Ok(config)
+    }
+
+    /// The admin deployments to register with: `targets`, or else the
+    /// `register` section as a single target named `default`.
+    #[must_use]
+    pub fn register_targets(&self) -> Vec<RegisterTarget> {
+        if self.targets.is_empty() {
+            vec![RegisterTarget {
+                name: DEFAULT_TARGET.to_string(),
+                register: self.register.clone(),
+            }]
+        } else {
+            self.targets.clone()
+        }
+    }
+
+    /// Create a [`ShenYuConfigBuilder`] starting from the defaults.
+    #[must_use]
+    pub fn builder() -> ShenYuConfigBuilder {
+        ShenYuConfigBuilder::default()
+    }
+
+    /// Serialize the configuration to YAML, in the layout read by
+    /// [`ShenYuConfig::from_yaml_file`].
+    pub fn to_yaml_string(&self) -> Result<String, ShenYuError> {
+        serde_yaml::to_string(&EnvConfig {
+            shenyu: self.clone(),
+        })
+        .map_err(|e| ShenYuError::serialization("write the shenyu config", e))
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, ShenYuError> {
+    std::fs::read_to_string(path)
+        .map_err(|e| ShenYuError::config(format!("read {}", path.display()), e))
 }
 
-#[allow(missing_docs)]
-#[derive(Debug, Deserialize)]
+/// Replace the placeholders of the parsed config from the environment.
+fn interpolate_env(value: &mut serde_yaml::Value, syntax: Placeholders) -> Result<(), ShenYuError> {
+    env::interpolate_value(value, &|name: &str| std::env::var(name).ok(), syntax)
+        .map_err(|e| ShenYuError::config("interpolate the config", e))
+}
+
+impl FromStr for ShenYuConfig {
+    type Err = ShenYuError;
+
+    /// Load configuration from a string, detecting its [`ConfigFormat`].
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        Self::from_str_with_format(contents, ConfigFormat::detect(contents))
+    }
+}
+
+/// How to reach and authenticate with shenyu admin.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct RegisterConfig {
+    /// The register type, only `http` is supported.
+    #[schemars(schema_with = "register_type")]
     pub register_type: String,
+    /// Comma separated shenyu admin urls, e.g. `http://127.0.0.1:9095`.
     pub servers: String,
+    /// Semicolon separated namespace ids, the default namespace if unset.
     pub namespace_id: Option<String>,
-    pub props: HashMap<String, String>,
+    /// The shenyu admin release, e.g. `2.6.1`, detected per admin server
+    /// if unset. See [`crate::version`].
+    pub admin_version: Option<String>,
+    /// The credentials logging in to shenyu admin, and any extra properties.
+    pub props: RegisterProps,
+    /// How requests to shenyu admin are authenticated, see [`AuthConfig`].
+    pub auth: AuthConfig,
+    /// Extra headers added to every request to shenyu admin, e.g. an API key.
+    pub headers: HashMap<String, Secret>,
+    /// TLS settings for `https://` servers.
+    pub tls: TlsConfig,
+    /// Timeouts of every request to shenyu admin.
+    pub timeout: TimeoutConfig,
+    /// The proxy requests to shenyu admin go through.
+    pub proxy: ProxyConfig,
+}
+
+impl Default for RegisterConfig {
+    fn default() -> Self {
+        Self {
+            register_type: "http".to_string(),
+            servers: "http://127.0.0.1:9095".to_string(),
+            namespace_id: None,
+            admin_version: None,
+            props: RegisterProps::default(),
+            auth: AuthConfig::default(),
+            headers: HashMap::new(),
+            tls: TlsConfig::default(),
+            timeout: TimeoutConfig::default(),
+            proxy: ProxyConfig::default(),
+        }
+    }
+}
+
+/// TLS settings for the connections to shenyu admin.
+///
+/// Without `ca_file`, server certificates are checked against the bundled
+/// Mozilla root certificates.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct TlsConfig {
+    /// A PEM bundle of the CA certificates trusted for shenyu admin,
+    /// replacing the default roots.
+    pub ca_file: Option<String>,
+    /// A PEM client certificate chain, presented for mutual TLS.
+    pub cert_file: Option<String>,
+    /// The PEM private key of `cert_file`.
+    pub key_file: Option<String>,
+    /// Accept any server certificate. Only meant for development.
+    pub insecure_skip_verify: bool,
+}
+
+/// Timeouts of the requests to shenyu admin, in milliseconds, `0` meaning
+/// no limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct TimeoutConfig {
+    /// The time allowed to establish a connection.
+    pub connect_ms: u64,
+    /// The time allowed for each read of the response.
+    pub read_ms: u64,
+    /// The time allowed for a whole request, response included.
+    pub overall_ms: u64,
 }
 
-#[allow(missing_docs)]
-#[derive(Debug, Deserialize)]
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self {
+            connect_ms: 5_000,
+            read_ms: 10_000,
+            overall_ms: 30_000,
+        }
+    }
+}
+
+/// An HTTP proxy for the requests to shenyu admin, `https://` ones being
+/// tunnelled with `CONNECT`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ProxyConfig {
+    /// The proxy url, e.g. `http://proxy.corp:3128`. No proxy if unset.
+    pub url: Option<String>,
+    /// The user name, if the proxy requires authentication.
+    pub username: String,
+    /// The password of `username`.
+    pub password: Secret,
+    /// Comma separated hosts reached directly, e.g. `localhost,.corp`: a
+    /// leading `.` matches subdomains and `*` every host. Defaults to the
+    /// `NO_PROXY` environment variable.
+    pub no_proxy: Option<String>,
+}
+
+/// How requests to shenyu admin are authenticated.
+///
+/// ```yaml
+/// auth: { mode: login }   # the default
+/// auth: { mode: token, header: Authorization, token: { env: ADMIN_BEARER } }
+/// auth: { mode: headers } # only `headers`, e.g. an API key
+/// auth: { mode: none }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum AuthConfig {
+    /// Log in at `/platform/login` with `props.username` and
+    /// `props.password`, sending the returned token as `X-Access-Token`.
+    #[default]
+    Login,
+    /// Send a fixed token.
+    Token {
+        /// The token, e.g. `Bearer <token>` for the `Authorization` header.
+        token: Secret,
+        /// The header carrying the token.
+        #[serde(default = "default_token_header")]
+        header: String,
+    },
+    /// Rely on `headers` alone.
+    Headers,
+    /// Send no credentials.
+    None,
+}
+
+/// The header shenyu admin reads its token from.
+pub const TOKEN_HEADER: &str = "X-Access-Token";
+
+fn default_token_header() -> String {
+    TOKEN_HEADER.to_string()
+}
+
+/// The `register.props` section.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct RegisterProps {
+    /// The shenyu admin user name.
+    pub username: String,
+    /// The shenyu admin password, inline or read from `{ env: NAME }` or
+    /// `{ file: PATH }`.
+    pub password: Secret,
+    /// Any other property.
+    #[serde(flatten, deserialize_with = "scalar_strings")]
+    #[schemars(schema_with = "scalar_map")]
+    pub extra: HashMap<String, String>,
+}
+
+impl From<HashMap<String, String>> for RegisterProps {
+    fn from(mut props: HashMap<String, String>) -> Self {
+        RegisterProps {
+            username: props.remove("username").unwrap_or_default(),
+            password: Secret::new(props.remove("password").unwrap_or_default()),
+            extra: props,
+        }
+    }
+}
+
+/// The name of the target made of the `register` section.
+pub const DEFAULT_TARGET: &str = "default";
+
+/// A named shenyu admin deployment, with its own servers, credentials and
+/// namespaces:
+///
+/// ```yaml
+/// shenyu:
+///   targets:
+///     - name: internal
+///       servers: http://admin.internal:9095
+///       props: { username: admin, password: { env: INTERNAL_ADMIN_PASSWORD } }
+///     - name: partner
+///       servers: https://admin.partner.example.com
+///       namespace_id: orders
+///       props: { username: orders, password: { env: PARTNER_ADMIN_PASSWORD } }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct RegisterTarget {
+    /// The target name, used in logs and registration reports.
+    pub name: String,
+    /// How to reach and authenticate with this deployment.
+    #[serde(flatten)]
+    pub register: RegisterConfig,
+}
+
+impl<'de> Deserialize<'de> for RegisterTarget {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut value = serde_yaml::Mapping::deserialize(deserializer)?;
+        let name = match value.remove("name") {
+            Some(serde_yaml::Value::String(name)) => name,
+            Some(_) => return Err(D::Error::custom("targets.name must be a string")),
+            None => String::new(),
+        };
+        // Through YAML text rather than `#[serde(flatten)]`, which would
+        // lose the lenient handling of scalars.
+        let yaml = serde_yaml::to_string(&value).map_err(D::Error::custom)?;
+        let register = serde_yaml::from_str(&yaml).map_err(D::Error::custom)?;
+        Ok(RegisterTarget { name, register })
+    }
+}
+
+/// What this service advertises to the gateway.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct UriConfig {
+    /// The application name.
     pub app_name: String,
+    /// The advertised host when `host_mode` is `literal`.
     pub host: String,
+    /// How the advertised host is determined, see [`HostMode`].
+    pub host_mode: HostMode,
+    /// The service port.
     pub port: u16,
+    /// The context path the gateway routes to this service, e.g. `/orders`.
     pub context_path: String,
+    /// The environment, selecting a profile of `shenyu.profiles` if any.
     pub environment: String,
+    /// The rpc type of the service.
+    #[schemars(schema_with = "rpc_type")]
     pub rpc_type: String,
 }
 
-#[allow(missing_docs)]
-#[derive(Debug, Deserialize)]
+impl Default for UriConfig {
+    fn default() -> Self {
+        Self {
+            app_name: String::new(),
+            host: String::new(),
+            host_mode: HostMode::default(),
+            port: 8080,
+            context_path: String::new(),
+            environment: String::new(),
+            rpc_type: "http".to_string(),
+        }
+    }
+}
+
+/// How the client determines the `host` it advertises to shenyu admin.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HostMode {
+    /// The local ip address detected from the network interfaces.
+    #[default]
+    Ip,
+    /// The machine hostname, as reported by the operating system.
+    Hostname,
+    /// The fully qualified domain name of the machine, resolved at startup
+    /// and checked to point back at a local address.
+    Fqdn,
+    /// The `uri.host` value, used verbatim.
+    Literal,
+}
+
+/// The discovery config registered for the service's selector.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
 pub struct DiscoveryConfig {
+    /// The upstream protocol, e.g. `http://`.
     pub protocol: String,
+    /// The discovery registry type.
+    #[schemars(schema_with = "discovery_type")]
     pub discovery_type: String,
+    /// The registry addresses, e.g. `127.0.0.1:2181`.
     pub server_lists: String,
+    /// The path the instances are registered under in the registry.
     pub register_path: String,
+    /// The plugin the discovery config belongs to.
     pub plugin_name: String,
+    /// Registry specific properties, e.g. `maxRetries`.
+    #[schemars(schema_with = "scalar_map")]
     pub props: HashMap<String, String>,
 }
 
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        Self {
+            protocol: "http://".to_string(),
+            discovery_type: "zookeeper".to_string(),
+            server_lists: "127.0.0.1:2181".to_string(),
+            register_path: String::new(),
+            plugin_name: String::new(),
+            props: HashMap::new(),
+        }
+    }
+}
+
+/// Deserialize a map whose values may be any scalar, which a flattened
+/// field needs since it no longer sees the YAML text.
+fn scalar_strings<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<String, String>, D::Error> {
+    let values = HashMap::<String, serde_yaml::Value>::deserialize(deserializer)?;
+    values
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                serde_yaml::Value::String(value) => value,
+                serde_yaml::Value::Number(value) => value.to_string(),
+                serde_yaml::Value::Bool(value) => value.to_string(),
+                serde_yaml::Value::Null => String::new(),
+                _ => return Err(D::Error::custom(format!("props.{key} must be a scalar"))),
+            };
+            Ok((key, value))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -88,10 +518,51 @@ mod tests {
     fn test_from_yaml_file() {
         let current_dir = env::current_dir().expect("Failed to get current directory");
         let config_path = current_dir.join("config.yml");
-        print!("config_path: {:?}", config_path);
+        print!("config_path: {config_path:?}");
         let config = ShenYuConfig::from_yaml_file(config_path.to_str().unwrap()).unwrap();
         assert_eq!(config.register.register_type, "http");
         assert_eq!(config.register.servers, "http://127.0.0.1:9095");
-        assert_eq!(config.register.props.len(), 2);
+        assert_eq!(config.register.props.username, "admin");
+        assert_eq!(config.register.props.password.expose(), "123456");
+        assert_eq!(config.uri.host_mode, HostMode::Ip);
+    }
+
+    #[test]
+    fn test_yaml_round_trip() {
+        let config = ShenYuConfig::from_yaml_file("config.yml").unwrap();
+        let yaml = config.to_yaml_string().unwrap();
+        let parsed: EnvConfig = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed.shenyu.register.servers, config.register.servers);
+        assert_eq!(parsed.shenyu.register.props, config.register.props);
+        assert_eq!(parsed.shenyu.uri.context_path, config.uri.context_path);
+        assert_eq!(parsed.shenyu.discovery.props, config.discovery.props);
+    }
+
+    #[test]
+    fn test_partial_yaml_uses_defaults() {
+        let yaml = "shenyu:\n  uri:\n    app_name: orders\n";
+        let config: EnvConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.shenyu.uri.app_name, "orders");
+        assert_eq!(config.shenyu.uri.rpc_type, "http");
+        assert_eq!(config.shenyu.register.servers, "http://127.0.0.1:9095");
+    }
+
+    #[test]
+    fn test_register_props() {
+        let yaml = "shenyu:\n  register:\n    props:\n      username: admin\n      password: 123456\n      retries: 3\n";
+        let config = ShenYuConfig::from_str_with_format(yaml, ConfigFormat::Yaml).unwrap();
+        let props = &config.register.props;
+        assert_eq!(props.password.expose(), "123456");
+        assert_eq!(props.extra["retries"], "3");
+        assert!(!format!("{config:?}").contains("123456"));
+    }
+
+    #[test]
+    fn test_host_mode() {
+        let mode: HostMode = serde_yaml::from_str("fqdn").unwrap();
+        assert_eq!(mode, HostMode::Fqdn);
+        let mode: HostMode = serde_yaml::from_str("literal").unwrap();
+        assert_eq!(mode, HostMode::Literal);
+        assert!(serde_yaml::from_str::<HostMode>("dns").is_err());
     }
 }
@@ -0,0 +1,255 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use super::{
+    AuthConfig, HostMode, ProxyConfig, RegisterConfig, RegisterTarget, ShenYuConfig, TimeoutConfig,
+    TlsConfig,
+};
+use crate::secret::Secret;
+
+/// A fluent builder for [`ShenYuConfig`], starting from its defaults.
+///
+/// # Examples
+/// ```rust
+/// use shenyu_client_rust::config::ShenYuConfig;
+///
+/// let config = ShenYuConfig::builder()
+///     .servers("http://127.0.0.1:9095")
+///     .register_prop("username", "admin")
+///     .register_prop("password", "123456")
+///     .app_name("orders")
+///     .context_path("/orders")
+///     .build();
+/// assert_eq!(config.uri.rpc_type, "http");
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ShenYuConfigBuilder {
+    config: ShenYuConfig,
+}
+
+#[allow(missing_docs)]
+impl ShenYuConfigBuilder {
+    #[must_use]
+    pub fn register_type(mut self, register_type: &str) -> Self {
+        self.config.register.register_type = register_type.to_string();
+        self
+    }
+
+    /// Comma separated shenyu admin urls.
+    #[must_use]
+    pub fn servers(mut self, servers: &str) -> Self {
+        self.config.register.servers = servers.to_string();
+        self
+    }
+
+    /// Semicolon separated namespace ids.
+    #[must_use]
+    pub fn namespace_id(mut self, namespace_id: &str) -> Self {
+        self.config.register.namespace_id = Some(namespace_id.to_string());
+        self
+    }
+
+    /// The shenyu admin release, e.g. `2.6.1`, instead of detecting it.
+    #[must_use]
+    pub fn admin_version(mut self, admin_version: &str) -> Self {
+        self.config.register.admin_version = Some(admin_version.to_string());
+        self
+    }
+
+    /// A `register.props` entry, `username` and `password` included.
+    #[must_use]
+    pub fn register_prop(mut self, key: &str, value: &str) -> Self {
+        let props = &mut self.config.register.props;
+        match key {
+            "username" => props.username = value.to_string(),
+            "password" => props.password = Secret::new(value),
+            _ => _ = props.extra.insert(key.to_string(), value.to_string()),
+        }
+        self
+    }
+
+    /// The shenyu admin password, e.g. read with [`Secret::from_file`].
+    #[must_use]
+    pub fn password(mut self, password: Secret) -> Self {
+        self.config.register.props.password = password;
+        self
+    }
+
+    /// How requests to shenyu admin are authenticated.
+    #[must_use]
+    pub fn auth(mut self, auth: AuthConfig) -> Self {
+        self.config.register.auth = auth;
+        self
+    }
+
+    /// A header added to every request to shenyu admin.
+    #[must_use]
+    pub fn header(mut self, name: &str, value: Secret) -> Self {
+        _ = self.config.register.headers.insert(name.to_string(), value);
+        self
+    }
+
+    /// TLS settings for `https://` admin servers.
+    #[must_use]
+    pub fn tls(mut self, tls: TlsConfig) -> Self {
+        self.config.register.tls = tls;
+        self
+    }
+
+    /// Timeouts of the requests to shenyu admin.
+    #[must_use]
+    pub fn timeout(mut self, timeout: TimeoutConfig) -> Self {
+        self.config.register.timeout = timeout;
+        self
+    }
+
+    /// The proxy requests to shenyu admin go through.
+    #[must_use]
+    pub fn proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.config.register.proxy = proxy;
+        self
+    }
+
+    /// Add a named admin deployment, see [`RegisterTarget`].
+    #[must_use]
+    pub fn target(mut self, name: &str, register: RegisterConfig) -> Self {
+        self.config.targets.push(RegisterTarget {
+            name: name.to_string(),
+            register,
+        });
+        self
+    }
+
+    #[must_use]
+    pub fn app_name(mut self, app_name: &str) -> Self {
+        self.config.uri.app_name = app_name.to_string();
+        self
+    }
+
+    #[must_use]
+    pub fn host(mut self, host: &str) -> Self {
+        self.config.uri.host = host.to_string();
+        self
+    }
+
+    #[must_use]
+    pub fn host_mode(mut self, host_mode: HostMode) -> Self {
+        self.config.uri.host_mode = host_mode;
+        self
+    }
+
+    #[must_use]
+    pub fn port(mut self, port: u16) -> Self {
+        self.config.uri.port = port;
+        self
+    }
+
+    #[must_use]
+    pub fn context_path(mut self, context_path: &str) -> Self {
+        self.config.uri.context_path = context_path.to_string();
+        self
+    }
+
+    #[must_use]
+    pub fn environment(mut self, environment: &str) -> Self {
+        self.config.uri.environment = environment.to_string();
+        self
+    }
+
+    #[must_use]
+    pub fn rpc_type(mut self, rpc_type: &str) -> Self {
+        self.config.uri.rpc_type = rpc_type.to_string();
+        self
+    }
+
+    #[must_use]
+    pub fn discovery_protocol(mut self, protocol: &str) -> Self {
+        self.config.discovery.protocol = protocol.to_string();
+        self
+    }
+
+    #[must_use]
+    pub fn discovery_type(mut self, discovery_type: &str) -> Self {
+        self.config.discovery.discovery_type = discovery_type.to_string();
+        self
+    }
+
+    #[must_use]
+    pub fn discovery_server_lists(mut self, server_lists: &str) -> Self {
+        self.config.discovery.server_lists = server_lists.to_string();
+        self
+    }
+
+    #[must_use]
+    pub fn discovery_register_path(mut self, register_path: &str) -> Self {
+        self.config.discovery.register_path = register_path.to_string();
+        self
+    }
+
+    #[must_use]
+    pub fn discovery_plugin_name(mut self, plugin_name: &str) -> Self {
+        self.config.discovery.plugin_name = plugin_name.to_string();
+        self
+    }
+
+    #[must_use]
+    pub fn discovery_prop(mut self, key: &str, value: &str) -> Self {
+        _ = self
+            .config
+            .discovery
+            .props
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> ShenYuConfig {
+        self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::EnvConfig;
+
+    #[test]
+    fn test_builder_round_trip() {
+        let config = ShenYuConfig::builder()
+            .servers("http://admin-1:9095,http://admin-2:9095")
+            .namespace_id("dev;test")
+            .register_prop("username", "admin")
+            .app_name("orders")
+            .host_mode(HostMode::Hostname)
+            .port(9000)
+            .context_path("/orders")
+            .discovery_type("nacos")
+            .discovery_prop("maxRetries", "4")
+            .build();
+        assert_eq!(config.register.register_type, "http");
+        assert_eq!(config.uri.port, 9000);
+
+        let yaml = config.to_yaml_string().unwrap();
+        let parsed: EnvConfig = serde_yaml::from_str(&yaml).unwrap();
+        let parsed = parsed.shenyu;
+        assert_eq!(parsed.register.servers, config.register.servers);
+        assert_eq!(parsed.register.namespace_id.as_deref(), Some("dev;test"));
+        assert_eq!(parsed.uri.host_mode, HostMode::Hostname);
+        assert_eq!(parsed.discovery.discovery_type, "nacos");
+        assert_eq!(parsed.discovery.props["maxRetries"], "4");
+    }
+}
@@ -0,0 +1,517 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use super::{AuthConfig, DiscoveryConfig, RegisterConfig, ShenYuConfig, UriConfig, TOKEN_HEADER};
+use crate::error::ShenYuError;
+use crate::secret::Secret;
+use serde_yaml::Value;
+use std::collections::HashMap;
+use tracing::warn;
+
+/// Prefix of the environment variables overriding config fields,
+/// e.g. `SHENYU_REGISTER_SERVERS` or `SHENYU_URI_CONTEXT_PATH`.
+pub const ENV_OVERRIDE_PREFIX: &str = "SHENYU_";
+
+/// The placeholder syntax of a config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Placeholders {
+    /// `${VAR}` and `${VAR:-default}`, as in the shell.
+    Shell,
+    /// Also Spring's `${VAR:default}`, used in the Java client's layout.
+    Spring,
+}
+
+/// Replace `${VAR}` and `${VAR:-default}` placeholders using `lookup`, and
+/// `${VAR:default}` ones with [`Placeholders::Spring`].
+///
+/// As in the shell, the default also applies when the variable is empty.
+/// `$${` is kept as a literal `${`. A placeholder without a default whose
+/// variable is not set is an error.
+pub(crate) fn interpolate<F>(
+    input: &str,
+    lookup: &F,
+    syntax: Placeholders,
+) -> Result<String, String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if let Some(escaped) = tail.strip_prefix("$${") {
+            output.push_str("${");
+            rest = escaped;
+            continue;
+        }
+        if !tail.starts_with("${") {
+            output.push('$');
+            rest = &tail[1..];
+            continue;
+        }
+        let end = tail
+            .find('}')
+            .ok_or_else(|| format!("unterminated placeholder: {tail}"))?;
+        let expr = &tail[2..end];
+        let spring = || match syntax {
+            Placeholders::Shell => None,
+            Placeholders::Spring => expr.split_once(':'),
+        };
+        let (name, default) = match expr.split_once(":-").or_else(spring) {
+            Some((name, default)) => (name, Some(default)),
+            None => (expr, None),
+        };
+        if name.is_empty() {
+            return Err(format!("empty placeholder: ${{{expr}}}"));
+        }
+        match (lookup(name), default) {
+            (Some(value), Some(default)) if value.is_empty() => output.push_str(default),
+            (Some(value), _) => output.push_str(&value),
+            (None, Some(default)) => output.push_str(default),
+            (None, None) => return Err(format!("environment variable {name} is not set")),
+        }
+        rest = &tail[end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Replace the placeholders in every string scalar of the parsed config
+/// `value`, see [`interpolate`].
+///
+/// Working on parsed values keeps substituted text, such as a password
+/// holding `#` or `: `, from changing the document's structure, and leaves
+/// comments and keys alone.
+pub(crate) fn interpolate_value<F>(
+    value: &mut Value,
+    lookup: &F,
+    syntax: Placeholders,
+) -> Result<(), String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    match value {
+        Value::String(scalar) if scalar.contains('$') => {
+            *value = typed(interpolate(scalar, lookup, syntax)?);
+        }
+        Value::Sequence(items) => {
+            for item in items {
+                interpolate_value(item, lookup, syntax)?;
+            }
+        }
+        Value::Mapping(mapping) => {
+            for (_, item) in mapping.iter_mut() {
+                interpolate_value(item, lookup, syntax)?;
+            }
+        }
+        Value::Tagged(tagged) => interpolate_value(&mut tagged.value, lookup, syntax)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// The interpolated `scalar` as an integer or boolean when it reads back
+/// unchanged, so `port: ${PORT}` still fills a numeric field, and as a
+/// string otherwise.
+fn typed(scalar: String) -> Value {
+    if let Ok(number) = scalar.parse::<i64>() {
+        if number.to_string() == scalar {
+            return Value::Number(number.into());
+        }
+    }
+    match scalar.as_str() {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => Value::String(scalar),
+    }
+}
+
+impl ShenYuConfig {
+    /// Apply `SHENYU_<SECTION>_<FIELD>` overrides from the process environment.
+    pub fn apply_env_overrides(&mut self) -> Result<(), ShenYuError> {
+        self.apply_overrides(std::env::vars())
+    }
+
+    /// Apply `SHENYU_<SECTION>_<FIELD>` overrides from `vars`.
+    ///
+    /// The section is one of `REGISTER`, `URI` or `DISCOVERY` and the field
+    /// is the upper-cased field name, e.g. `SHENYU_URI_CONTEXT_PATH`.
+    /// Entries of `props` are addressed as `SHENYU_REGISTER_PROPS_PASSWORD`,
+    /// and the fields of a `targets` entry as `SHENYU_TARGETS_<NAME>_SERVERS`.
+    /// Variables outside these sections are ignored, and those naming no
+    /// field are logged and skipped. A value that doesn't fit its field is
+    /// an error. `*_AUTH_MODE` is applied before the other variables, so
+    /// the token and header it takes can be given in any order.
+    pub fn apply_overrides<I>(&mut self, vars: I) -> Result<(), ShenYuError>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let mut vars: Vec<(String, String)> = vars.into_iter().collect();
+        vars.sort_by_key(|(name, _)| !name.ends_with("_AUTH_MODE"));
+        for (name, value) in vars {
+            let Some(key) = name.strip_prefix(ENV_OVERRIDE_PREFIX) else {
+                continue;
+            };
+            let key = key.to_ascii_lowercase();
+            let result = if let Some(field) = key.strip_prefix("register_") {
+                self.register.set_field(field, value)
+            } else if let Some(field) = key.strip_prefix("uri_") {
+                self.uri.set_field(field, value)
+            } else if let Some(field) = key.strip_prefix("discovery_") {
+                self.discovery.set_field(field, value)
+            } else if let Some(field) = key.strip_prefix("targets_") {
+                self.set_target_field(field, value)
+            } else {
+                continue;
+            };
+            match result {
+                Ok(()) => {}
+                Err(Override::Unknown) => {
+                    warn!(variable = %name, "ignoring an override naming no config field");
+                }
+                Err(Override::Invalid(e)) => {
+                    return Err(ShenYuError::config(format!("apply {name}"), e));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Why an override wasn't applied.
+#[derive(Debug)]
+enum Override {
+    /// No field is named by the variable.
+    Unknown,
+    /// The value doesn't fit the field.
+    Invalid(String),
+}
+
+impl From<String> for Override {
+    fn from(e: String) -> Self {
+        Override::Invalid(e)
+    }
+}
+
+impl ShenYuConfig {
+    /// Set `<name>_<field>` of a target, its name upper-cased with `-` and
+    /// `.` written as `_`.
+    fn set_target_field(&mut self, key: &str, value: String) -> Result<(), Override> {
+        let target = self
+            .targets
+            .iter_mut()
+            .filter_map(|target| {
+                let prefix =
+                    format!("{}_", target.name.to_ascii_lowercase()).replace(['-', '.'], "_");
+                let field = key.strip_prefix(&prefix)?.to_string();
+                Some((field, target))
+            })
+            .min_by_key(|(field, _)| field.len());
+        let Some((field, target)) = target else {
+            return Err(Override::Unknown);
+        };
+        target.register.set_field(&field, value)
+    }
+}
+
+impl RegisterConfig {
+    fn set_field(&mut self, field: &str, value: String) -> Result<(), Override> {
+        match field {
+            "register_type" => self.register_type = value,
+            "servers" => self.servers = value,
+            "namespace_id" => self.namespace_id = Some(value),
+            "admin_version" => self.admin_version = Some(value),
+            "props_username" => self.props.username = value,
+            "props_password" => self.props.password = Secret::new(value),
+            "auth_mode" => {
+                self.auth = match value.as_str() {
+                    "login" => AuthConfig::Login,
+                    "token" if matches!(self.auth, AuthConfig::Token { .. }) => return Ok(()),
+                    "token" => AuthConfig::Token {
+                        token: Secret::default(),
+                        header: TOKEN_HEADER.to_string(),
+                    },
+                    "headers" => AuthConfig::Headers,
+                    "none" => AuthConfig::None,
+                    _ => return Err(format!("unknown auth mode {value}").into()),
+                };
+            }
+            "tls_ca_file" => self.tls.ca_file = Some(value),
+            "tls_cert_file" => self.tls.cert_file = Some(value),
+            "tls_key_file" => self.tls.key_file = Some(value),
+            "tls_insecure_skip_verify" => {
+                self.tls.insecure_skip_verify = value
+                    .parse()
+                    .map_err(|_| format!("invalid boolean {value}"))?;
+            }
+            "timeout_connect_ms" | "timeout_read_ms" | "timeout_overall_ms" => {
+                let millis = value
+                    .parse()
+                    .map_err(|_| format!("invalid timeout {value}"))?;
+                match field {
+                    "timeout_connect_ms" => self.timeout.connect_ms = millis,
+                    "timeout_read_ms" => self.timeout.read_ms = millis,
+                    _ => self.timeout.overall_ms = millis,
+                }
+            }
+            "proxy_url" => self.proxy.url = Some(value),
+            "proxy_username" => self.proxy.username = value,
+            "proxy_password" => self.proxy.password = Secret::new(value),
+            "proxy_no_proxy" => self.proxy.no_proxy = Some(value),
+            "auth_token" | "auth_header" => {
+                let AuthConfig::Token { token, header } = &mut self.auth else {
+                    return Err(format!("{field} requires auth mode token").into());
+                };
+                if field == "auth_token" {
+                    *token = Secret::new(value);
+                } else {
+                    *header = value;
+                }
+            }
+            _ => {
+                if let Some(name) = field.strip_prefix("headers_") {
+                    set_header(&mut self.headers, name, value);
+                    return Ok(());
+                }
+                return set_prop(&mut self.props.extra, field, value);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl UriConfig {
+    fn set_field(&mut self, field: &str, value: String) -> Result<(), Override> {
+        match field {
+            "app_name" => self.app_name = value,
+            "host" => self.host = value,
+            "host_mode" => {
+                self.host_mode = serde_yaml::from_str(&value)
+                    .map_err(|_| format!("unknown host mode {value}"))?;
+            }
+            "port" => {
+                self.port = value.parse().map_err(|_| format!("invalid port {value}"))?;
+            }
+            "context_path" => self.context_path = value,
+            "environment" => self.environment = value,
+            "rpc_type" => self.rpc_type = value,
+            _ => return Err(Override::Unknown),
+        }
+        Ok(())
+    }
+}
+
+impl DiscoveryConfig {
+    fn set_field(&mut self, field: &str, value: String) -> Result<(), Override> {
+        match field {
+            "protocol" => self.protocol = value,
+            "discovery_type" => self.discovery_type = value,
+            "server_lists" => self.server_lists = value,
+            "register_path" => self.register_path = value,
+            "plugin_name" => self.plugin_name = value,
+            _ => return set_prop(&mut self.props, field, value),
+        }
+        Ok(())
+    }
+}
+
+/// Set `headers_<name>`, matching an existing header whose `-` are written
+/// as `_`, or else adding one with `_` turned into `-`.
+fn set_header(headers: &mut HashMap<String, Secret>, name: &str, value: String) {
+    let name = headers
+        .keys()
+        .find(|existing| existing.replace('-', "_").eq_ignore_ascii_case(name))
+        .cloned()
+        .unwrap_or_else(|| name.replace('_', "-"));
+    _ = headers.insert(name, Secret::new(value));
+}
+
+/// Set `props_<key>`, matching an existing key case-insensitively since
+/// environment variable names can't carry the camelCase of prop names.
+fn set_prop(
+    props: &mut HashMap<String, String>,
+    field: &str,
+    value: String,
+) -> Result<(), Override> {
+    let key = field
+        .strip_prefix("props_")
+        .filter(|key| !key.is_empty())
+        .ok_or(Override::Unknown)?;
+    let key = props
+        .keys()
+        .find(|existing| existing.eq_ignore_ascii_case(key))
+        .cloned()
+        .unwrap_or_else(|| key.to_string());
+    _ = props.insert(key, value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RegisterTarget;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "ADMIN_PASSWORD" => Some("s3cret".to_string()),
+            "EMPTY" => Some(String::new()),
+            "TRICKY_PASSWORD" => Some("p#ss: 'w0rd".to_string()),
+            "PORT" => Some("9000".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_interpolate() {
+        assert_eq!(
+            interpolate("password: ${ADMIN_PASSWORD}", &lookup, Placeholders::Shell).unwrap(),
+            "password: s3cret"
+        );
+        assert_eq!(
+            interpolate(
+                "servers: ${ADMIN_SERVERS:-http://127.0.0.1:9095}",
+                &lookup,
+                Placeholders::Shell
+            )
+            .unwrap(),
+            "servers: http://127.0.0.1:9095"
+        );
+        assert_eq!(
+            interpolate("a: ${EMPTY:-x}", &lookup, Placeholders::Shell).unwrap(),
+            "a: x"
+        );
+        assert_eq!(
+            interpolate("a: ${EMPTY}", &lookup, Placeholders::Shell).unwrap(),
+            "a: "
+        );
+        assert_eq!(
+            interpolate(
+                "a: ${MISSING:localhost:2181}",
+                &lookup,
+                Placeholders::Spring
+            )
+            .unwrap(),
+            "a: localhost:2181"
+        );
+        assert!(interpolate("a: ${URL:http://x}", &lookup, Placeholders::Shell).is_err());
+        assert_eq!(
+            interpolate("a: $${HOME} $5", &lookup, Placeholders::Shell).unwrap(),
+            "a: ${HOME} $5"
+        );
+        assert!(interpolate("a: ${MISSING}", &lookup, Placeholders::Shell).is_err());
+        assert!(interpolate("a: ${ADMIN_PASSWORD", &lookup, Placeholders::Shell).is_err());
+    }
+
+    #[test]
+    fn test_interpolate_value() {
+        let yaml = "
+shenyu:
+  register:
+    # ${UNSET} is not read from comments
+    props:
+      password: ${TRICKY_PASSWORD}
+  uri:
+    port: ${PORT}
+    context_path: /orders-${PORT}
+";
+        let mut value: Value = serde_yaml::from_str(yaml).unwrap();
+        interpolate_value(&mut value, &lookup, Placeholders::Shell).unwrap();
+        let yaml = serde_yaml::to_string(&value).unwrap();
+        let config = serde_yaml::from_str::<crate::config::EnvConfig>(&yaml)
+            .unwrap()
+            .shenyu;
+        assert_eq!(config.register.props.password.expose(), "p#ss: 'w0rd");
+        assert_eq!(config.uri.port, 9000);
+        assert_eq!(config.uri.context_path, "/orders-9000");
+        assert_eq!(typed("007".to_string()), Value::String("007".to_string()));
+    }
+
+    #[test]
+    fn test_apply_overrides() {
+        let mut config = ShenYuConfig::from_yaml_file("config.yml").unwrap();
+        let vars = [
+            ("SHENYU_REGISTER_SERVERS", "http://admin:9095"),
+            ("SHENYU_REGISTER_PROPS_PASSWORD", "s3cret"),
+            ("SHENYU_URI_CONTEXT_PATH", "/orders"),
+            ("SHENYU_URI_PORT", "9000"),
+            ("SHENYU_DISCOVERY_PROPS_MAXRETRIES", "8"),
+            ("SHENYU_PROFILE", "ignored"),
+            ("PATH", "/usr/bin"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        config.apply_overrides(vars).unwrap();
+        assert_eq!(config.register.servers, "http://admin:9095");
+        assert_eq!(config.register.props.password.expose(), "s3cret");
+        assert_eq!(config.uri.context_path, "/orders");
+        assert_eq!(config.uri.port, 9000);
+        assert_eq!(config.discovery.props["maxRetries"], "8");
+
+        config.targets = vec![RegisterTarget {
+            name: "partner-eu".to_string(),
+            register: RegisterConfig::default(),
+        }];
+        let target = [("SHENYU_TARGETS_PARTNER_EU_NAMESPACE_ID", "orders")]
+            .map(|(k, v)| (k.to_string(), v.to_string()));
+        config.apply_overrides(target).unwrap();
+        assert_eq!(
+            config.targets[0].register.namespace_id.as_deref(),
+            Some("orders")
+        );
+
+        let auth = [
+            ("SHENYU_REGISTER_AUTH_MODE", "token"),
+            ("SHENYU_REGISTER_AUTH_TOKEN", "t0ken"),
+            ("SHENYU_REGISTER_HEADERS_X_API_KEY", "k3y"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        config.apply_overrides(auth).unwrap();
+        assert!(matches!(
+            &config.register.auth,
+            AuthConfig::Token { token, header } if token.expose() == "t0ken" && header == TOKEN_HEADER
+        ));
+        assert_eq!(config.register.headers["x-api-key"].expose(), "k3y");
+
+        let mut reversed = ShenYuConfig::default();
+        let auth = [
+            ("SHENYU_REGISTER_AUTH_HEADER", "X-Token"),
+            ("SHENYU_REGISTER_AUTH_TOKEN", "t0ken"),
+            ("SHENYU_REGISTER_AUTH_MODE", "token"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        reversed.apply_overrides(auth).unwrap();
+        let mode =
+            [("SHENYU_REGISTER_AUTH_MODE", "token")].map(|(k, v)| (k.to_string(), v.to_string()));
+        reversed.apply_overrides(mode).unwrap();
+        assert!(matches!(
+            &reversed.register.auth,
+            AuthConfig::Token { token, header } if token.expose() == "t0ken" && header == "X-Token"
+        ));
+
+        let bad = [("SHENYU_URI_PORT".to_string(), "http".to_string())];
+        assert!(config.apply_overrides(bad).is_err());
+        let before = config.clone();
+        let unknown = [
+            ("SHENYU_URI_NOPE", "x"),
+            ("SHENYU_DISCOVERY_NOPE", "x"),
+            ("SHENYU_TARGETS_UNKNOWN_SERVERS", "http://x:9095"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+        config.apply_overrides(unknown).unwrap();
+        assert_eq!(config, before);
+    }
+}
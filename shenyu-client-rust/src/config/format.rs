@@ -0,0 +1,284 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use crate::error::BoxError;
+use serde_yaml::{Mapping, Value};
+use std::path::Path;
+
+/// The file formats `ShenYuConfig` can be loaded from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// YAML, the layout of `config.yml`.
+    Yaml,
+    /// TOML, with a `[shenyu.register]`, `[shenyu.uri]`... layout.
+    Toml,
+    /// JSON, with a top-level `shenyu` object.
+    Json,
+    /// Java `.properties`, with Spring-style keys such as `shenyu.register.servers`.
+    Properties,
+}
+
+impl ConfigFormat {
+    /// Guess the format from the file extension.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "yml" | "yaml" => Some(Self::Yaml),
+            "toml" => Some(Self::Toml),
+            "json" => Some(Self::Json),
+            "properties" => Some(Self::Properties),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from the file contents.
+    #[must_use]
+    pub fn detect(contents: &str) -> Self {
+        let first = contents
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('!'))
+            .unwrap_or_default();
+        if first.starts_with('{') {
+            return Self::Json;
+        }
+        if first.starts_with('[') {
+            return Self::Toml;
+        }
+        match (first.find('='), first.find(':')) {
+            (Some(eq), colon) if colon.map_or(true, |colon| eq < colon) => {
+                // Dotted keys are valid in both, only TOML requires quoted strings.
+                if toml::from_str::<toml::Value>(contents).is_ok() {
+                    Self::Toml
+                } else {
+                    Self::Properties
+                }
+            }
+            _ => Self::Yaml,
+        }
+    }
+
+    /// Parse `contents` into a YAML value tree.
+    pub(crate) fn parse(self, contents: &str) -> Result<Value, BoxError> {
+        Ok(match self {
+            Self::Yaml => serde_yaml::from_str(contents)?,
+            Self::Toml => serde_yaml::to_value(toml::from_str::<toml::Value>(contents)?)?,
+            Self::Json => serde_json::from_str(contents)?,
+            Self::Properties => parse_properties(contents)?,
+        })
+    }
+}
+
+/// Parse Java `.properties` into a nested tree, splitting keys on `.`.
+///
+/// Values that look like numbers or booleans are kept as such, so they
+/// deserialize into numeric fields the same way YAML scalars do.
+fn parse_properties(contents: &str) -> Result<Value, String> {
+    let mut root = Mapping::new();
+    for (key, value) in properties_entries(contents) {
+        let mut node = &mut root;
+        let mut segments = key.split('.').peekable();
+        while let Some(segment) = segments.next() {
+            if segment.is_empty() {
+                return Err(format!("invalid property key {key}"));
+            }
+            let segment = Value::String(segment.to_string());
+            if segments.peek().is_none() {
+                _ = node.insert(segment, scalar(&value));
+                break;
+            }
+            let child = node
+                .entry(segment)
+                .or_insert_with(|| Value::Mapping(Mapping::new()));
+            node = child
+                .as_mapping_mut()
+                .ok_or_else(|| format!("property {key} conflicts with a value of its parent"))?;
+        }
+    }
+    Ok(Value::Mapping(root))
+}
+
+fn scalar(value: &str) -> Value {
+    match serde_yaml::from_str::<Value>(value) {
+        Ok(parsed @ (Value::Number(_) | Value::Bool(_))) => parsed,
+        _ => Value::String(value.to_string()),
+    }
+}
+
+/// Split `.properties` contents into unescaped `(key, value)` pairs,
+/// handling comments, `=`/`:`/whitespace separators and line continuations.
+fn properties_entries(contents: &str) -> Vec<(String, String)> {
+    let mut entries = Vec::new();
+    let mut logical = String::new();
+    for line in contents.lines() {
+        let line = line.trim_start();
+        if logical.is_empty() && (line.is_empty() || line.starts_with('#') || line.starts_with('!'))
+        {
+            continue;
+        }
+        let trailing = line.chars().rev().take_while(|c| *c == '\\').count();
+        if trailing % 2 == 1 {
+            logical.push_str(&line[..line.len() - 1]);
+            continue;
+        }
+        logical.push_str(line);
+        entries.push(split_entry(&std::mem::take(&mut logical)));
+    }
+    if !logical.is_empty() {
+        entries.push(split_entry(&logical));
+    }
+    entries
+}
+
+fn split_entry(line: &str) -> (String, String) {
+    let mut key = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    key.push(unescape(escaped));
+                }
+            }
+            '=' | ':' => break,
+            c if c.is_whitespace() => {
+                let rest = chars.as_str().trim_start();
+                let rest = rest.strip_prefix(['=', ':']).map_or(rest, str::trim_start);
+                return (key, unescape_value(rest));
+            }
+            c => key.push(c),
+        }
+    }
+    (key, unescape_value(chars.as_str().trim_start()))
+}
+
+fn unescape_value(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('u') => {
+                let code: String = chars.by_ref().take(4).collect();
+                if let Some(decoded) = u32::from_str_radix(&code, 16).ok().and_then(char::from_u32)
+                {
+                    output.push(decoded);
+                }
+            }
+            Some(escaped) => output.push(unescape(escaped)),
+            None => {}
+        }
+    }
+    output
+}
+
+fn unescape(c: char) -> char {
+    match c {
+        't' => '\t',
+        'n' => '\n',
+        'r' => '\r',
+        'f' => '\u{c}',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ShenYuConfig;
+
+    const TOML: &str = r#"
+[shenyu.register]
+register_type = "http"
+servers = "http://127.0.0.1:9095"
+props = { username = "admin", password = 123456 }
+
+[shenyu.uri]
+app_name = "orders"
+port = 8000
+context_path = "/orders"
+
+[shenyu.discovery.props]
+maxRetries = 4
+"#;
+
+    const JSON: &str = r#"{
+  "shenyu": {
+    "register": {"servers": "http://127.0.0.1:9095", "props": {"username": "admin"}},
+    "uri": {"app_name": "orders", "port": 8000, "context_path": "/orders"}
+  }
+}"#;
+
+    const PROPERTIES: &str = r"
+# Spring style
+shenyu.register.servers=http://127.0.0.1:9095,\
+  http://127.0.0.1:9096
+shenyu.register.props.username = admin
+shenyu.register.props.password: 123456
+shenyu.uri.app_name orders
+shenyu.uri.port=8000
+shenyu.uri.context_path=/orders
+shenyu.discovery.props.maxRetries=4
+";
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(ConfigFormat::detect(TOML), ConfigFormat::Toml);
+        assert_eq!(ConfigFormat::detect(JSON), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::detect(PROPERTIES), ConfigFormat::Properties);
+        assert_eq!(
+            ConfigFormat::detect("shenyu:\n  uri:\n    port: 8000\n"),
+            ConfigFormat::Yaml
+        );
+        assert_eq!(
+            ConfigFormat::from_path(Path::new("/etc/shenyu/Config.TOML")),
+            Some(ConfigFormat::Toml)
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        let config: ShenYuConfig = TOML.parse().unwrap();
+        assert_eq!(config.register.props.password.expose(), "123456");
+        assert_eq!(config.uri.port, 8000);
+        assert_eq!(config.discovery.props["maxRetries"], "4");
+
+        let config: ShenYuConfig = JSON.parse().unwrap();
+        assert_eq!(config.uri.context_path, "/orders");
+
+        let config: ShenYuConfig = PROPERTIES.parse().unwrap();
+        assert_eq!(
+            config.register.servers,
+            "http://127.0.0.1:9095,http://127.0.0.1:9096"
+        );
+        assert_eq!(config.register.props.password.expose(), "123456");
+        assert_eq!(config.uri.app_name, "orders");
+        assert_eq!(config.uri.port, 8000);
+        assert_eq!(config.discovery.props["maxRetries"], "4");
+    }
+
+    #[test]
+    fn test_from_absolute_path() {
+        let path = std::env::current_dir().unwrap().join("config.yml");
+        let config = ShenYuConfig::from_path(&path).unwrap();
+        assert_eq!(config.register.servers, "http://127.0.0.1:9095");
+    }
+}
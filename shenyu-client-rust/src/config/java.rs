@@ -0,0 +1,226 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! The layout of the Java client's `application.yml`:
+//!
+//! ```yaml
+//! shenyu:
+//!   register:
+//!     registerType: http
+//!     serverLists: http://localhost:9095
+//!     props:
+//!       username: admin
+//!       password: 123456
+//!   client:
+//!     http:
+//!       props:
+//!         contextPath: /http
+//!         appName: http
+//!         port: 8189
+//!   namespace: 649330b6-c2d7-4edc-be8e-8a54df9eb385
+//!   discovery:
+//!     type: zookeeper
+//!     serverList: localhost:2181
+//!     registerPath: /shenyu/discovery/http_example
+//! ```
+
+use super::ShenYuConfig;
+use serde::Deserialize;
+use serde_yaml::Value;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct JavaEnvConfig {
+    shenyu: JavaShenYuConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct JavaShenYuConfig {
+    register: JavaRegisterConfig,
+    client: BTreeMap<String, JavaClientConfig>,
+    namespace: Option<String>,
+    discovery: JavaDiscoveryConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct JavaRegisterConfig {
+    register_type: Option<String>,
+    server_lists: Option<String>,
+    props: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct JavaClientConfig {
+    props: HashMap<String, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct JavaDiscoveryConfig {
+    #[serde(rename = "type")]
+    discovery_type: Option<String>,
+    server_list: Option<String>,
+    register_path: Option<String>,
+    protocol: Option<String>,
+    plugin_name: Option<String>,
+    props: HashMap<String, String>,
+}
+
+/// Whether `value` uses the Java client's camelCase layout rather than
+/// this crate's, judged by the keys only the Java client uses.
+pub(crate) fn is_java_layout(value: &Value) -> bool {
+    let shenyu = &value["shenyu"];
+    let register = &shenyu["register"];
+    !register["registerType"].is_null()
+        || !register["serverLists"].is_null()
+        || !shenyu["client"].is_null()
+}
+
+/// Map the Java client's layout onto [`ShenYuConfig`], keeping the defaults
+/// for anything the Java file doesn't set.
+pub(crate) fn from_yaml_str(contents: &str) -> Result<ShenYuConfig, serde_yaml::Error> {
+    let java = serde_yaml::from_str::<JavaEnvConfig>(contents)?.shenyu;
+    let mut config = ShenYuConfig::default();
+
+    let register = &mut config.register;
+    if let Some(register_type) = java.register.register_type {
+        register.register_type = register_type;
+    }
+    if let Some(servers) = java.register.server_lists {
+        register.servers = servers;
+    }
+    register.props = java.register.props.into();
+    register.namespace_id = java
+        .namespace
+        .map(|namespace| namespace.replace(',', ";"))
+        .filter(|namespace| !namespace.is_empty());
+
+    // The Rust client registers a single rpc type, prefer http if the
+    // Java file configures several.
+    let client = java
+        .client
+        .get_key_value("http")
+        .or_else(|| java.client.iter().next());
+    if let Some((rpc_type, client)) = client {
+        let uri = &mut config.uri;
+        uri.rpc_type.clone_from(rpc_type);
+        let props = &client.props;
+        if let Some(app_name) = props.get("appName") {
+            uri.app_name.clone_from(app_name);
+        }
+        if let Some(context_path) = props.get("contextPath") {
+            uri.context_path.clone_from(context_path);
+        }
+        if let Some(host) = props.get("host") {
+            uri.host.clone_from(host);
+        }
+        if let Some(port) = props.get("port").and_then(|port| port.parse().ok()) {
+            uri.port = port;
+        }
+    }
+
+    let discovery = &mut config.discovery;
+    let java_discovery = java.discovery;
+    if let Some(discovery_type) = java_discovery.discovery_type {
+        discovery.discovery_type = discovery_type;
+    }
+    if let Some(server_lists) = java_discovery.server_list {
+        discovery.server_lists = server_lists;
+    }
+    if let Some(register_path) = java_discovery.register_path {
+        discovery.register_path = register_path;
+    }
+    if let Some(protocol) = java_discovery.protocol {
+        discovery.protocol = protocol;
+    }
+    if let Some(plugin_name) = java_discovery.plugin_name {
+        discovery.plugin_name = plugin_name;
+    }
+    discovery.props = java_discovery.props;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigFormat;
+
+    const APPLICATION_YML: &str = r"
+server:
+  port: 8189
+shenyu:
+  register:
+    registerType: http
+    serverLists: http://localhost:9095
+    props:
+      username: admin
+      password: 123456
+  client:
+    http:
+      props:
+        contextPath: /http
+        appName: http-example
+        port: 8189
+        isFull: false
+  namespace: 649330b6-c2d7-4edc-be8e-8a54df9eb385,testNamespaceId
+  discovery:
+    type: zookeeper
+    serverList: ${REGISTER_DISCOVERY_SERVER_LIST_UNSET:localhost:2181}
+    registerPath: /shenyu/discovery/http_example
+    props:
+      maxRetries: 4
+";
+
+    #[test]
+    fn test_java_layout() {
+        let config =
+            ShenYuConfig::from_str_with_format(APPLICATION_YML, ConfigFormat::Yaml).unwrap();
+        assert_eq!(config.register.register_type, "http");
+        assert_eq!(config.register.servers, "http://localhost:9095");
+        assert_eq!(config.register.props.password.expose(), "123456");
+        assert_eq!(
+            config.register.namespace_id.as_deref(),
+            Some("649330b6-c2d7-4edc-be8e-8a54df9eb385;testNamespaceId")
+        );
+        assert_eq!(config.uri.rpc_type, "http");
+        assert_eq!(config.uri.app_name, "http-example");
+        assert_eq!(config.uri.context_path, "/http");
+        assert_eq!(config.uri.port, 8189);
+        assert_eq!(config.discovery.server_lists, "localhost:2181");
+        assert_eq!(config.discovery.props["maxRetries"], "4");
+    }
+
+    #[test]
+    fn test_java_properties() {
+        let properties = "shenyu.register.serverLists=http://admin:9095\n\
+                          shenyu.client.springCloud.props.contextPath=/springcloud\n";
+        let config: ShenYuConfig = properties.parse().unwrap();
+        assert_eq!(config.register.servers, "http://admin:9095");
+        assert_eq!(config.uri.rpc_type, "springCloud");
+        assert_eq!(config.uri.context_path, "/springcloud");
+    }
+
+    #[test]
+    fn test_rust_layout_is_not_java() {
+        let value: Value = serde_yaml::from_str("shenyu:\n  register:\n    servers: x\n").unwrap();
+        assert!(!is_java_layout(&value));
+    }
+}
@@ -0,0 +1,205 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use super::env::{interpolate, Placeholders};
+use serde_yaml::{Mapping, Value};
+
+/// Environment variable selecting the profile, taking precedence over
+/// `uri.environment`.
+pub const PROFILE_ENV: &str = "SHENYU_PROFILE";
+
+/// The override of `uri.environment`, which selects the profile as well.
+const ENVIRONMENT_ENV: &str = "SHENYU_URI_ENVIRONMENT";
+
+/// The profile selected by the environment: [`PROFILE_ENV`], or else the
+/// override of `uri.environment`.
+pub(crate) fn selected<F>(lookup: &F) -> Option<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    [PROFILE_ENV, ENVIRONMENT_ENV]
+        .into_iter()
+        .filter_map(lookup)
+        .find(|name| !name.is_empty())
+}
+
+/// Merge the selected profile of `shenyu.profiles` into the base config.
+///
+/// The profile is `selected` if given, `uri.environment` otherwise, its
+/// placeholders replaced using `lookup`. Each profile is a partial config:
+/// mappings are merged key by key, anything else replaces the base value.
+/// The `profiles` section is removed either way, before the rest of the
+/// config is interpolated, so the profiles not selected may reference
+/// variables that are not set.
+pub(crate) fn apply_profile<F>(
+    value: &mut Value,
+    selected: Option<&str>,
+    lookup: &F,
+    syntax: Placeholders,
+) -> Result<(), String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let Some(shenyu) = value.get_mut("shenyu").and_then(Value::as_mapping_mut) else {
+        return Ok(());
+    };
+    let Some(profiles) = shenyu.remove("profiles") else {
+        return Ok(());
+    };
+    let environment = shenyu
+        .get("uri")
+        .and_then(|uri| uri.get("environment"))
+        .and_then(Value::as_str)
+        .map(|environment| interpolate(environment, lookup, syntax))
+        .transpose()?;
+    let Some(name) = selected
+        .map(ToString::to_string)
+        .or(environment)
+        .filter(|name| !name.is_empty())
+    else {
+        return Ok(());
+    };
+
+    let Some(overlay) = profiles.get(name.as_str()) else {
+        let known: Vec<&str> = profiles
+            .as_mapping()
+            .map(|profiles| profiles.keys().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        return Err(format!(
+            "profile `{name}` is not defined in shenyu.profiles, known profiles: {known:?}"
+        ));
+    };
+    let mut base = Value::Mapping(std::mem::take(shenyu));
+    merge(&mut base, overlay.clone());
+    // Keep the selected name, so `environment` reflects the active profile.
+    let mut uri = Mapping::new();
+    _ = uri.insert("environment".into(), name.into());
+    let mut pinned = Mapping::new();
+    _ = pinned.insert("uri".into(), Value::Mapping(uri));
+    merge(&mut base, Value::Mapping(pinned));
+    if let Value::Mapping(merged) = base {
+        *shenyu = merged;
+    }
+    Ok(())
+}
+
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => _ = base.insert(key, value),
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ConfigFormat, EnvConfig, ShenYuConfig};
+
+    const CONFIG: &str = r"
+shenyu:
+  register:
+    servers: http://127.0.0.1:9095
+    namespace_id: dev
+    props:
+      username: admin
+      password: '123456'
+  uri:
+    context_path: /orders
+    environment: dev
+  discovery:
+    server_lists: 127.0.0.1:2181
+  profiles:
+    dev: {}
+    prod:
+      register:
+        servers: http://admin-1.prod:9095,http://admin-2.prod:9095
+        namespace_id: prod
+        props:
+          password: prod-secret
+      discovery:
+        discovery_type: nacos
+        server_lists: http://nacos.prod:8848
+";
+
+    fn load(selected: Option<&str>) -> Result<EnvConfig, String> {
+        let mut value: Value = serde_yaml::from_str(CONFIG).unwrap();
+        apply_profile(&mut value, selected, &|_| None, Placeholders::Shell)?;
+        Ok(serde_yaml::from_value(value).unwrap())
+    }
+
+    #[test]
+    fn test_environment_selects_profile() {
+        let config = load(None).unwrap().shenyu;
+        assert_eq!(config.register.servers, "http://127.0.0.1:9095");
+        assert_eq!(config.uri.environment, "dev");
+    }
+
+    #[test]
+    fn test_overlay_profile() {
+        let config = load(Some("prod")).unwrap().shenyu;
+        assert_eq!(
+            config.register.servers,
+            "http://admin-1.prod:9095,http://admin-2.prod:9095"
+        );
+        assert_eq!(config.register.namespace_id.as_deref(), Some("prod"));
+        assert_eq!(config.register.props.username, "admin");
+        assert_eq!(config.register.props.password.expose(), "prod-secret");
+        assert_eq!(config.uri.context_path, "/orders");
+        assert_eq!(config.uri.environment, "prod");
+        assert_eq!(config.discovery.discovery_type, "nacos");
+    }
+
+    #[test]
+    fn test_environment_override_selects_profile() {
+        let lookup = |name: &str| (name == ENVIRONMENT_ENV).then(|| "prod".to_string());
+        assert_eq!(selected(&lookup).as_deref(), Some("prod"));
+        let lookup = |name: &str| match name {
+            PROFILE_ENV => Some("staging".to_string()),
+            _ => Some("prod".to_string()),
+        };
+        assert_eq!(selected(&lookup).as_deref(), Some("staging"));
+        assert_eq!(selected(&|_| None), None);
+    }
+
+    #[test]
+    fn test_other_profiles_are_not_interpolated() {
+        let config = CONFIG.replace("prod-secret", "${SHENYU_TEST_UNSET_PROD_SECRET}");
+        let config = ShenYuConfig::from_str_with_format(&config, ConfigFormat::Yaml).unwrap();
+        assert_eq!(config.register.servers, "http://127.0.0.1:9095");
+
+        let mut value: Value = serde_yaml::from_str(&CONFIG.replace(
+            "environment: dev",
+            "environment: ${SHENYU_TEST_UNSET_ENVIRONMENT:-prod}",
+        ))
+        .unwrap();
+        apply_profile(&mut value, None, &|_| None, Placeholders::Shell).unwrap();
+        assert_eq!(value["shenyu"]["register"]["namespace_id"], "prod");
+    }
+
+    #[test]
+    fn test_unknown_profile() {
+        let error = load(Some("staging")).unwrap_err();
+        assert!(error.contains("staging"));
+    }
+}
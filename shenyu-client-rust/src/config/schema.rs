@@ -0,0 +1,173 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use super::{EnvConfig, ShenYuConfig, DISCOVERY_TYPES, REGISTER_TYPES, RPC_TYPES};
+use schemars::{json_schema, Schema, SchemaGenerator};
+use serde_json::Value;
+
+impl EnvConfig {
+    /// The JSON Schema of the configuration file, with its `shenyu` root.
+    #[must_use]
+    pub fn json_schema() -> Value {
+        schemars::schema_for!(EnvConfig).to_value()
+    }
+}
+
+impl ShenYuConfig {
+    /// The JSON Schema of the `shenyu` section of the configuration file.
+    #[must_use]
+    pub fn json_schema() -> Value {
+        schemars::schema_for!(ShenYuConfig).to_value()
+    }
+}
+
+pub(crate) fn register_type(_: &mut SchemaGenerator) -> Schema {
+    string_enum(REGISTER_TYPES)
+}
+
+pub(crate) fn rpc_type(_: &mut SchemaGenerator) -> Schema {
+    string_enum(RPC_TYPES)
+}
+
+pub(crate) fn discovery_type(_: &mut SchemaGenerator) -> Schema {
+    string_enum(DISCOVERY_TYPES)
+}
+
+/// A map of props, whose values may be written as any YAML scalar.
+pub(crate) fn scalar_map(_: &mut SchemaGenerator) -> Schema {
+    json_schema!({
+        "type": "object",
+        "additionalProperties": {
+            "type": ["string", "number", "boolean"]
+        }
+    })
+}
+
+/// Describe `profiles`, the overlays by profile name, each a partial
+/// `shenyu` section: every section optional, without its defaults.
+pub(crate) fn profiles(schema: &mut Schema) {
+    let Some(Value::Object(mut sections)) = schema.get("properties").cloned() else {
+        return;
+    };
+    for section in sections.values_mut().filter_map(Value::as_object_mut) {
+        _ = section.remove("default");
+    }
+    let profiles = json_schema!({
+        "description": "Partial configs by profile name, the selected one merged over the rest of the `shenyu` section.",
+        "type": "object",
+        "additionalProperties": {
+            "type": "object",
+            "properties": sections
+        }
+    });
+    if let Some(properties) = schema.get_mut("properties").and_then(Value::as_object_mut) {
+        _ = properties.insert("profiles".to_string(), profiles.to_value());
+    }
+}
+
+fn string_enum(values: &[&str]) -> Schema {
+    json_schema!({
+        "type": "string",
+        "enum": values
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_schema() {
+        let schema = EnvConfig::json_schema();
+        let definitions = &schema["$defs"];
+        assert!(schema["properties"]["shenyu"].is_object());
+
+        let uri = &definitions["UriConfig"]["properties"];
+        assert_eq!(uri["rpc_type"]["enum"], serde_json::json!(RPC_TYPES));
+        assert_eq!(
+            uri["context_path"]["description"],
+            "The context path the gateway routes to this service, e.g. `/orders`."
+        );
+        let register = &definitions["RegisterConfig"]["properties"];
+        assert_eq!(
+            register["register_type"]["enum"],
+            serde_json::json!(REGISTER_TYPES)
+        );
+        assert!(register["register_type"]["description"].is_string());
+        let discovery = &definitions["DiscoveryConfig"]["properties"];
+        assert_eq!(
+            discovery["discovery_type"]["enum"],
+            serde_json::json!(DISCOVERY_TYPES)
+        );
+        assert!(definitions["HostMode"].is_object());
+    }
+
+    #[test]
+    fn test_schema_covers_every_field() {
+        let schema = ShenYuConfig::json_schema();
+        let yaml = ShenYuConfig::default().to_yaml_string().unwrap();
+        let config: Value = serde_yaml::from_str(&yaml).unwrap();
+        for (section, fields) in config["shenyu"].as_object().unwrap() {
+            let name = schema["properties"][section]["$ref"]
+                .as_str()
+                .unwrap()
+                .trim_start_matches("#/$defs/");
+            let properties = &schema["$defs"][name]["properties"];
+            for field in fields.as_object().unwrap().keys() {
+                assert!(properties[field].is_object(), "{section}.{field}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_schema_covers_profiles() {
+        let yaml = r"
+shenyu:
+  uri:
+    environment: prod
+  profiles:
+    dev: {}
+    prod:
+      register:
+        servers: http://admin.prod:9095
+        props:
+          password: prod-secret
+      uri:
+        host: 10.0.0.1
+      discovery:
+        discovery_type: nacos
+";
+        let schema = EnvConfig::json_schema();
+        let config: Value = serde_yaml::from_str(yaml).unwrap();
+        let reference = &schema["$defs"]["ShenYuConfig"]["properties"]["profiles"];
+        assert_eq!(reference["type"], "object");
+        let overlay = &reference["additionalProperties"]["properties"];
+        for (name, profile) in config["shenyu"]["profiles"].as_object().unwrap() {
+            for (section, fields) in profile.as_object().unwrap() {
+                assert!(overlay[section]["default"].is_null(), "{name}.{section}");
+                let definition = overlay[section]["$ref"]
+                    .as_str()
+                    .unwrap()
+                    .trim_start_matches("#/$defs/");
+                let properties = &schema["$defs"][definition]["properties"];
+                for field in fields.as_object().unwrap().keys() {
+                    assert!(properties[field].is_object(), "{name}.{section}.{field}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,463 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use super::{AuthConfig, HostMode, RegisterConfig, ShenYuConfig};
+use crate::servers::{self, ServerSource};
+use crate::version::AdminVersion;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use url::Url;
+
+/// The register types supported by this client.
+pub const REGISTER_TYPES: &[&str] = &["http"];
+
+/// The rpc types known to shenyu admin.
+pub const RPC_TYPES: &[&str] = &[
+    "http",
+    "springCloud",
+    "dubbo",
+    "sofa",
+    "tars",
+    "motan",
+    "grpc",
+    "websocket",
+    "brpc",
+];
+
+/// The discovery types known to shenyu admin.
+pub const DISCOVERY_TYPES: &[&str] = &["zookeeper", "nacos", "eureka", "etcd", "local"];
+
+/// A single problem found in the configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// The path of the offending field, e.g. `register.servers`.
+    pub field: String,
+    /// What is wrong and how to fix it.
+    pub message: String,
+}
+
+impl Display for ConfigIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "shenyu.{}: {}", self.field, self.message)
+    }
+}
+
+/// Every problem found by [`ShenYuConfig::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigErrors {
+    /// The problems, in field order.
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl Display for ConfigErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid shenyu config")?;
+        for issue in &self.issues {
+            write!(f, "\n  - {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigErrors {}
+
+#[derive(Default)]
+struct Issues(Vec<ConfigIssue>);
+
+impl Issues {
+    fn push(&mut self, field: &str, message: String) {
+        self.0.push(ConfigIssue {
+            field: field.to_string(),
+            message,
+        });
+    }
+}
+
+impl ShenYuConfig {
+    /// Check the whole configuration, reporting every problem at once.
+    pub fn validate(&self) -> Result<(), ConfigErrors> {
+        let mut issues = Issues::default();
+        self.validate_register(&mut issues);
+        self.validate_uri(&mut issues);
+        self.validate_discovery(&mut issues);
+        if issues.0.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigErrors { issues: issues.0 })
+        }
+    }
+
+    fn validate_register(&self, issues: &mut Issues) {
+        if self.targets.is_empty() {
+            self.register.validate_into("register", issues);
+            return;
+        }
+        let mut names = HashSet::new();
+        for target in &self.targets {
+            if target.name.is_empty() {
+                issues.push("targets.name", "every target needs a name".to_string());
+            } else if !names.insert(target.name.as_str()) {
+                issues.push(
+                    "targets.name",
+                    format!("`{}` names more than one target", target.name),
+                );
+            }
+            target
+                .register
+                .validate_into(&format!("targets.{}", target.name), issues);
+        }
+    }
+}
+
+impl RegisterConfig {
+    fn validate_into(&self, section: &str, issues: &mut Issues) {
+        let register = self;
+        if !REGISTER_TYPES.contains(&register.register_type.as_str()) {
+            issues.push(
+                &format!("{section}.register_type"),
+                format!(
+                    "unsupported register type `{}`, expected one of {REGISTER_TYPES:?}",
+                    register.register_type
+                ),
+            );
+        }
+
+        let sources = servers::sources(&register.servers);
+        if sources.is_empty() {
+            issues.push(
+                &format!("{section}.servers"),
+                "is empty, set it to the shenyu admin url(s), e.g. `http://127.0.0.1:9095`"
+                    .to_string(),
+            );
+        }
+        for source in sources {
+            let checked = match &source {
+                ServerSource::Url(url) | ServerSource::List(url) => check_admin_url(url),
+                ServerSource::Srv { scheme, name } => check_srv(scheme, name),
+                ServerSource::File(path) if path.is_empty() => Err("has no path".to_string()),
+                ServerSource::File(_) => Ok(()),
+            };
+            if let Err(message) = checked {
+                issues.push(
+                    &format!("{section}.servers"),
+                    format!("`{source}` {message}"),
+                );
+            }
+        }
+
+        if let Some(namespace_id) = register.namespace_id.as_deref() {
+            for namespace in namespace_id.split(';').filter(|ns| !ns.is_empty()) {
+                if !namespace
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                {
+                    issues.push(
+                        &format!("{section}.namespace_id"),
+                        format!(
+                            "`{namespace}` is not a valid namespace id, \
+                             separate several ids with `;`"
+                        ),
+                    );
+                }
+            }
+        }
+
+        if let Some(Err(message)) = register
+            .admin_version
+            .as_deref()
+            .map(str::parse::<AdminVersion>)
+        {
+            issues.push(&format!("{section}.admin_version"), message);
+        }
+
+        register.validate_auth(section, issues);
+        let tls = &register.tls;
+        if tls.cert_file.is_some() != tls.key_file.is_some() {
+            issues.push(
+                &format!("{section}.tls"),
+                "cert_file and key_file must be set together".to_string(),
+            );
+        }
+        match register.proxy.url.as_deref().map(Url::parse) {
+            Some(Ok(url)) if url.scheme() != "http" || url.host_str().is_none() => issues.push(
+                &format!("{section}.proxy.url"),
+                format!("`{url}` must be an `http://` proxy url"),
+            ),
+            Some(Err(e)) => issues.push(
+                &format!("{section}.proxy.url"),
+                format!("is not a valid url: {e}"),
+            ),
+            _ => {}
+        }
+        if tls.insecure_skip_verify && tls.ca_file.is_some() {
+            issues.push(
+                &format!("{section}.tls"),
+                "ca_file is ignored when insecure_skip_verify is set".to_string(),
+            );
+        }
+        for name in register.headers.keys() {
+            if !is_header_name(name) {
+                issues.push(
+                    &format!("{section}.headers"),
+                    format!("`{name}` is not a valid header name"),
+                );
+            }
+        }
+    }
+
+    fn validate_auth(&self, section: &str, issues: &mut Issues) {
+        match &self.auth {
+            AuthConfig::Login => {
+                let credentials = [
+                    ("username", self.props.username.is_empty()),
+                    ("password", self.props.password.is_empty()),
+                ];
+                for (key, missing) in credentials {
+                    if missing {
+                        issues.push(
+                            &format!("{section}.props.{key}"),
+                            "is required to log in to shenyu admin".to_string(),
+                        );
+                    }
+                }
+            }
+            AuthConfig::Token { token, header } => {
+                if token.is_empty() {
+                    issues.push(
+                        &format!("{section}.auth.token"),
+                        "is required for auth mode `token`".to_string(),
+                    );
+                }
+                if !is_header_name(header) {
+                    issues.push(
+                        &format!("{section}.auth.header"),
+                        format!("`{header}` is not a valid header name"),
+                    );
+                }
+            }
+            AuthConfig::Headers => {
+                if self.headers.is_empty() {
+                    issues.push(
+                        &format!("{section}.headers"),
+                        "is required for auth mode `headers`".to_string(),
+                    );
+                }
+            }
+            AuthConfig::None => {}
+        }
+    }
+}
+
+impl ShenYuConfig {
+    fn validate_uri(&self, issues: &mut Issues) {
+        let uri = &self.uri;
+        let context_path = uri.context_path.as_str();
+        if !context_path.starts_with('/') {
+            issues.push(
+                "uri.context_path",
+                format!("`{context_path}` must start with `/`, e.g. `/orders`"),
+            );
+        } else if context_path.len() > 1 && context_path.ends_with('/') {
+            issues.push(
+                "uri.context_path",
+                format!("`{context_path}` must not end with `/`"),
+            );
+        }
+        if context_path.chars().any(char::is_whitespace) {
+            issues.push(
+                "uri.context_path",
+                format!("`{context_path}` must not contain whitespace"),
+            );
+        }
+
+        if !RPC_TYPES.contains(&uri.rpc_type.as_str()) {
+            issues.push(
+                "uri.rpc_type",
+                format!(
+                    "unknown rpc type `{}`, expected one of {RPC_TYPES:?}",
+                    uri.rpc_type
+                ),
+            );
+        }
+
+        if uri.host_mode == HostMode::Literal && uri.host.trim().is_empty() {
+            issues.push(
+                "uri.host",
+                "is required when host_mode is `literal`".to_string(),
+            );
+        }
+    }
+
+    fn validate_discovery(&self, issues: &mut Issues) {
+        let discovery = &self.discovery;
+        if !DISCOVERY_TYPES.contains(&discovery.discovery_type.as_str()) {
+            issues.push(
+                "discovery.discovery_type",
+                format!(
+                    "unknown discovery type `{}`, expected one of {DISCOVERY_TYPES:?}",
+                    discovery.discovery_type
+                ),
+            );
+        } else if discovery.discovery_type != "local" && discovery.server_lists.trim().is_empty() {
+            issues.push(
+                "discovery.server_lists",
+                format!("is required for {} discovery", discovery.discovery_type),
+            );
+        }
+    }
+}
+
+/// Whether `name` is a valid HTTP header name.
+fn is_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+}
+
+/// Check the scheme and name of an `srv+<scheme>://<name>` entry.
+fn check_srv(scheme: &str, name: &str) -> Result<(), String> {
+    if !matches!(scheme, "http" | "https") {
+        return Err(format!("has scheme `{scheme}`, expected `http` or `https`"));
+    }
+    if name.is_empty() || name.contains(['/', ':', ' ']) {
+        return Err("is not a DNS SRV name, e.g. `_shenyu-admin._tcp.example.com`".to_string());
+    }
+    Ok(())
+}
+
+/// Check that `server` is an absolute http(s) url with a host.
+pub(crate) fn check_admin_url(server: &str) -> Result<(), String> {
+    let url = Url::parse(server).map_err(|e| format!("is not a valid url: {e}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!(
+            "has scheme `{}`, expected `http` or `https`",
+            url.scheme()
+        ));
+    }
+    if url.host_str().map_or(true, str::is_empty) {
+        return Err("has no host".to_string());
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err("must not have a query or fragment".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secret::Secret;
+
+    #[test]
+    fn test_valid_config() {
+        let config = ShenYuConfig::from_yaml_file("config.yml").unwrap();
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_reports_every_issue() {
+        let config = ShenYuConfig::builder()
+            .servers("http://127.0.0.1:9095, ftp://admin, not a url")
+            .namespace_id("dev;bad id")
+            .register_prop("username", "admin")
+            .context_path("orders/")
+            .rpc_type("rest")
+            .host_mode(HostMode::Literal)
+            .build();
+        let errors = config.validate().unwrap_err();
+        let fields: Vec<&str> = errors.issues.iter().map(|i| i.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            [
+                "register.servers",
+                "register.servers",
+                "register.namespace_id",
+                "register.props.password",
+                "uri.context_path",
+                "uri.rpc_type",
+                "uri.host",
+            ]
+        );
+        assert!(errors
+            .to_string()
+            .contains("shenyu.register.props.password"));
+    }
+
+    #[test]
+    fn test_empty_servers() {
+        let config = ShenYuConfig::builder()
+            .servers(" , ")
+            .register_prop("username", "admin")
+            .register_prop("password", "123456")
+            .context_path("/orders")
+            .build();
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors.issues.len(), 1);
+        assert_eq!(errors.issues[0].field, "register.servers");
+    }
+
+    #[test]
+    fn test_targets() {
+        let yaml = r"
+shenyu:
+  uri:
+    context_path: /orders
+  targets:
+    - name: internal
+      servers: http://admin.internal:9095
+      props: { username: admin, password: 123456 }
+    - name: internal
+      servers: https://admin.partner.example.com
+      namespace_id: orders
+      props: { username: orders }
+";
+        let config: ShenYuConfig = yaml.parse().unwrap();
+        assert_eq!(config.targets[0].register.props.password.expose(), "123456");
+        assert_eq!(config.register_targets().len(), 2);
+        let errors = config.validate().unwrap_err();
+        let fields: Vec<&str> = errors.issues.iter().map(|i| i.field.as_str()).collect();
+        assert_eq!(fields, ["targets.name", "targets.internal.props.password"]);
+    }
+
+    #[test]
+    fn test_auth_modes() {
+        let base = ShenYuConfig::builder().context_path("/orders");
+        let config = base.clone().auth(AuthConfig::None).build();
+        assert!(config.validate().is_ok());
+
+        let config = base.clone().auth(AuthConfig::Headers).build();
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors.issues[0].field, "register.headers");
+        let config = base
+            .clone()
+            .auth(AuthConfig::Headers)
+            .header("X-Api-Key", Secret::new("k3y"))
+            .build();
+        assert!(config.validate().is_ok());
+
+        let config = base
+            .auth(AuthConfig::Token {
+                token: Secret::default(),
+                header: "Bad Header".to_string(),
+            })
+            .build();
+        let errors = config.validate().unwrap_err();
+        let fields: Vec<&str> = errors.issues.iter().map(|i| i.field.as_str()).collect();
+        assert_eq!(fields, ["register.auth.token", "register.auth.header"]);
+    }
+}
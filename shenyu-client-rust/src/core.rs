@@ -15,15 +15,33 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use crate::config::ShenYuConfig;
+use crate::config::{AuthConfig, RegisterConfig, RegisterTarget, ShenYuConfig, TOKEN_HEADER};
+use crate::diagnose::{self, Check, CheckKind, CheckStatus, Diagnosis};
 use crate::error::ShenYuError;
-use crate::model::{EventType, UriInfo};
-use dashmap::DashMap;
+use crate::host::resolve_host;
+use crate::journal::{AuditJournal, AuditRecord};
+use crate::listener::{AdminEvent, Login, RegistrationListener};
+#[cfg(feature = "metrics")]
+use crate::metrics::ClientMetrics;
+use crate::model::{
+    DiscoveryConfigRegistration, EventType, MetadataRegistration, UriInfo, UriRegistration,
+};
+use crate::secret::REDACTED;
+use crate::servers::{self, ServerSource};
+use crate::status::{ClientStatus, StatusBoard};
+use crate::transport::{self, Transport};
+use crate::version::AdminVersion;
+use dashmap::{DashMap, DashSet};
+use serde::Serialize;
 use serde_json::Value;
-use std::collections::HashMap;
-use std::io::{Error, ErrorKind};
-use std::net::IpAddr;
-use tracing::{error, info, warn};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::sync::{PoisonError, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, field, info, info_span, warn, Span};
 use ureq::OrAnyStatus;
 
 /// Shenyu admin http interface path.
@@ -41,18 +59,53 @@ pub const REGISTER_OFFLINE_SUFFIX: &str = "/shenyu-client/offline";
 /// Shenyu admin http interface path.
 pub const PLATFORM_LOGIN_SUFFIX: &str = "/platform/login";
 
+/// Shenyu admin http interface path.
+pub const NAMESPACE_LIST_SUFFIX: &str = "/namespace/findAll";
+
 /// Shenyu admin default namespace id.
 pub const SYS_DEFAULT_NAMESPACE_ID: &str = "649330b6-c2d7-4edc-be8e-8a54df9eb385";
 
 /// The shenyu client.
-#[derive(Debug)]
 #[warn(dead_code)]
 pub struct ShenyuClient {
     pub(super) headers: DashMap<String, String>,
+    /// The admin token of each target, by target name.
+    tokens: DashMap<String, String>,
     app_name: String,
-    env: ShenYuConfig,
-    host: Option<String>,
     port: u16,
+    uri_infos: Vec<UriInfo>,
+    state: RwLock<ClientState>,
+    listeners: Vec<Arc<dyn RegistrationListener>>,
+    /// The target, server and namespace of the heartbeats failing in a row.
+    lost_heartbeats: DashSet<(String, String, String)>,
+    /// Whether the client went offline, which stops the heartbeat until it
+    /// registers again.
+    offline: AtomicBool,
+    status: StatusBoard,
+    journal: Option<Arc<AuditJournal>>,
+    /// The release detected per admin server.
+    admin_versions: DashMap<String, AdminVersion>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<ClientMetrics>>,
+}
+
+/// Everything the client derives from its `ShenYuConfig`, replaced as a
+/// whole when the config is reloaded.
+#[derive(Debug, Clone)]
+struct ClientState {
+    env: ShenYuConfig,
+    host: String,
+    targets: Vec<TargetState>,
+}
+
+/// The admin urls and namespaces of one [`RegisterTarget`].
+#[derive(Debug, Clone)]
+struct TargetState {
+    name: String,
+    register: RegisterConfig,
+    transport: Transport,
+    /// The configured admin release, detected per server if unset.
+    admin_version: Option<AdminVersion>,
     namespace_ids: Vec<String>,
     gateway_base_urls: Vec<String>,
     register_meta_data_path_list: Vec<String>,
@@ -60,27 +113,92 @@ pub struct ShenyuClient {
     register_token_servers: Vec<String>,
     register_discover_config_servers: Vec<String>,
     register_offline_servers: Vec<String>,
-    uri_infos: Vec<UriInfo>,
+}
+
+/// What a config reload changes in the registration with shenyu admin.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RegistrationDiff {
+    /// Targets that are no longer configured.
+    pub removed_targets: Vec<String>,
+    /// Admin servers that are no longer configured in a kept target.
+    pub removed_servers: Vec<String>,
+    /// Namespaces that are no longer configured in a kept target.
+    pub removed_namespaces: Vec<String>,
+    /// Whether the advertised uri (host, context path or rpc type) changed,
+    /// which invalidates the previous registration on every admin.
+    pub uri_changed: bool,
+    /// Whether anything sent to shenyu admin changed.
+    pub changed: bool,
+}
+
+/// The outcome of a registration call, per target.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RegistrationReport {
+    /// One report per target, in config order.
+    pub targets: Vec<TargetReport>,
+}
+
+impl RegistrationReport {
+    /// Whether every request to every target succeeded.
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        self.targets.iter().all(TargetReport::is_success)
+    }
+
+    /// The report of the target named `name`.
+    #[must_use]
+    pub fn target(&self, name: &str) -> Option<&TargetReport> {
+        self.targets.iter().find(|target| target.name == name)
+    }
+}
+
+/// The outcome of a registration call on one target.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TargetReport {
+    /// The target name.
+    pub name: String,
+    /// How many requests shenyu admin accepted.
+    pub succeeded: usize,
+    /// A description of every request that failed.
+    pub failures: Vec<String>,
+}
+
+impl TargetReport {
+    fn new(name: &str) -> Self {
+        TargetReport {
+            name: name.to_string(),
+            ..TargetReport::default()
+        }
+    }
+
+    /// Whether every request to this target succeeded.
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
 }
 
 impl ShenyuClient {
-    /// Register to shenyu admin.
-    pub fn register(&self) -> Result<(), Error> {
-        if let Ok(token) = self.get_register_token() {
-            info!(
-                "[SUCCESS], get register token success, register token: {:#?}",
-                &token
-            );
-            _ = self
-                .headers
-                .insert("X-Access-Token".to_string(), token.to_string());
-        } else {
-            error!("Can't get register token");
+    /// Register to every shenyu admin target.
+    ///
+    /// Fails when no target accepted any request, the report of a partial
+    /// registration otherwise.
+    pub fn register(&self) -> Result<RegistrationReport, ShenYuError> {
+        self.offline.store(false, Ordering::Release);
+        let state = self.state();
+        let report = self.register_state(&state);
+        if report.targets.iter().all(|target| target.succeeded == 0) {
+            let failures: Vec<&str> = report
+                .targets
+                .iter()
+                .flat_map(|target| target.failures.iter().map(String::as_str))
+                .collect();
+            return Err(ShenYuError::transport(
+                "register with shenyu admin",
+                failures.join("; "),
+            ));
         }
-        self.register_all_metadata(true);
-        self.register_uri();
-        self.register_discovery_config();
-        Ok(())
+        Ok(report)
     }
 
     /// Create a new `ShenyuClient`.
@@ -89,13 +207,340 @@ impl ShenyuClient {
         app_name: &str,
         uri_infos: &[UriInfo],
         port: u16,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, ShenYuError> {
         let headers = DashMap::new();
         _ = headers.insert(
             "Content-Type".to_string(),
             "application/json;charset=UTF-8".to_string(),
         );
-        let namespace_ids: Vec<String> = config
+
+        Ok(ShenyuClient {
+            headers,
+            tokens: DashMap::new(),
+            app_name: app_name.to_string(),
+            port,
+            uri_infos: uri_infos.to_owned(),
+            state: RwLock::new(ClientState::new(config)?),
+            listeners: vec![],
+            lost_heartbeats: DashSet::new(),
+            offline: AtomicBool::new(false),
+            status: StatusBoard::default(),
+            journal: None,
+            admin_versions: DashMap::new(),
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        })
+    }
+
+    /// Call `listener` on the registration lifecycle events, after the
+    /// listeners added before.
+    #[must_use]
+    pub fn listener(mut self, listener: Arc<dyn RegistrationListener>) -> Self {
+        self.listeners.push(listener);
+        self
+    }
+
+    /// Write every registration, heartbeat and offline call to `journal`.
+    #[must_use]
+    pub fn journal(mut self, journal: Arc<AuditJournal>) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Record the requests to shenyu admin in `metrics`.
+    #[cfg(feature = "metrics")]
+    #[must_use]
+    pub fn metrics(mut self, metrics: Arc<ClientMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Apply a new configuration without restarting the service.
+    ///
+    /// Registrations that no longer apply, on removed targets or admin
+    /// servers, in removed namespaces or under the previous uri, are taken
+    /// offline, then the new state is registered. Nothing is sent if the
+    /// registration is unchanged, or if the client went offline.
+    pub fn reload(&self, config: ShenYuConfig) -> Result<RegistrationDiff, ShenYuError> {
+        let new_state = ClientState::new(config)?;
+        let old_state = self.state();
+        let diff = RegistrationDiff::between(&old_state, &new_state);
+        if !diff.changed || self.offline.load(Ordering::Acquire) {
+            *self.state.write().unwrap_or_else(PoisonError::into_inner) = new_state;
+            self.evict_servers(&self.state());
+            return Ok(diff);
+        }
+        info!("[RELOAD], shenyu config changed: {:?}", diff);
+
+        for old in &old_state.targets {
+            let mut report = TargetReport::new(&old.name);
+            match new_state.target(&old.name) {
+                Some(new) if !diff.uri_changed => {
+                    let (kept_servers, removed_servers): (Vec<String>, Vec<String>) = old
+                        .gateway_base_urls
+                        .iter()
+                        .cloned()
+                        .partition(|server| new.gateway_base_urls.contains(server));
+                    let removed_namespaces: Vec<String> = old
+                        .namespace_ids
+                        .iter()
+                        .filter(|namespace| !new.namespace_ids.contains(namespace))
+                        .cloned()
+                        .collect();
+                    self.offline_state(
+                        &old_state,
+                        old,
+                        &removed_servers,
+                        &old.namespace_ids,
+                        &mut report,
+                    );
+                    self.offline_state(
+                        &old_state,
+                        old,
+                        &kept_servers,
+                        &removed_namespaces,
+                        &mut report,
+                    );
+                }
+                _ => self.offline_state(
+                    &old_state,
+                    old,
+                    &old.gateway_base_urls,
+                    &old.namespace_ids,
+                    &mut report,
+                ),
+            }
+        }
+        for name in &diff.removed_targets {
+            _ = self.tokens.remove(name);
+        }
+
+        *self.state.write().unwrap_or_else(PoisonError::into_inner) = new_state;
+        let state = self.state();
+        self.evict_servers(&state);
+        _ = self.register_state(&state);
+        Ok(diff)
+    }
+
+    /// Resolve the `servers` of every target again, for those listing DNS
+    /// SRV names, files or urls, see [`crate::servers`].
+    ///
+    /// The admin url lists are rebuilt in place, the registration is taken
+    /// offline from the servers that dropped out and sent to those that
+    /// appeared. A target whose servers fail to resolve keeps its current
+    /// list. Nothing is sent if the client went offline.
+    pub fn refresh_servers(&self) -> RegistrationReport {
+        if self.offline.load(Ordering::Acquire) {
+            return RegistrationReport::default();
+        }
+        let old_state = self.state();
+        let mut added: Vec<Option<TargetState>> = vec![];
+        let mut removed: Vec<(&TargetState, Vec<String>)> = vec![];
+        let mut refreshed: Vec<TargetState> = vec![];
+        for target in &old_state.targets {
+            let sources = servers::sources(&target.register.servers);
+            if !sources.iter().any(ServerSource::is_dynamic) {
+                continue;
+            }
+            let urls = match servers::resolve(&target.register.servers, &target.transport) {
+                Ok(urls) if urls != target.gateway_base_urls => urls,
+                Ok(_) => continue,
+                Err(e) => {
+                    warn!(
+                        "[REFRESH], keeping the servers of target {}: {e}",
+                        target.name
+                    );
+                    continue;
+                }
+            };
+            info!(
+                "[REFRESH], servers of target {} changed to {urls:?}",
+                target.name
+            );
+            let new_urls: Vec<String> = urls
+                .iter()
+                .filter(|url| !target.gateway_base_urls.contains(url))
+                .cloned()
+                .collect();
+            let old_urls: Vec<String> = target
+                .gateway_base_urls
+                .iter()
+                .filter(|url| !urls.contains(url))
+                .cloned()
+                .collect();
+            let old_target = target;
+            let mut target = target.clone();
+            if target.set_up_gateway_service_url(urls).is_err() {
+                continue;
+            }
+            let mut new_servers = target.clone();
+            added.push(
+                (!new_urls.is_empty() && new_servers.set_up_gateway_service_url(new_urls).is_ok())
+                    .then_some(new_servers),
+            );
+            removed.push((old_target, old_urls));
+            refreshed.push(target);
+        }
+        if refreshed.is_empty() {
+            return RegistrationReport::default();
+        }
+
+        let state = {
+            let mut state = self.state.write().unwrap_or_else(PoisonError::into_inner);
+            for target in refreshed {
+                // Unless a reload replaced the target meanwhile.
+                if let Some(current) = state.targets.iter_mut().find(|current| {
+                    current.name == target.name && current.register == target.register
+                }) {
+                    *current = target;
+                }
+            }
+            state.clone()
+        };
+        self.evict_servers(&state);
+        let targets = removed
+            .iter()
+            .zip(&added)
+            .map(|((old, old_urls), added)| {
+                let mut report = TargetReport::new(&old.name);
+                self.offline_state(&old_state, old, old_urls, &old.namespace_ids, &mut report);
+                if let Some(added) = added {
+                    let registered = self.register_target(&state, added);
+                    report.succeeded += registered.succeeded;
+                    report.failures.extend(registered.failures);
+                }
+                report
+            })
+            .collect();
+        RegistrationReport { targets }
+    }
+
+    /// Forget what was learned about the admin servers no target of `state`
+    /// lists anymore.
+    fn evict_servers(&self, state: &ClientState) {
+        let listed = |server: &str| {
+            state
+                .targets
+                .iter()
+                .any(|target| target.gateway_base_urls.iter().any(|url| url == server))
+        };
+        self.admin_versions.retain(|server, _| listed(server));
+        self.lost_heartbeats.retain(|(_, server, _)| listed(server));
+    }
+
+    /// The registration state of every target and namespace currently
+    /// configured.
+    #[must_use]
+    pub fn status(&self) -> ClientStatus {
+        let state = self.state();
+        let namespaces = state
+            .targets
+            .iter()
+            .flat_map(|target| {
+                target
+                    .namespace_ids
+                    .iter()
+                    .map(|namespace_id| self.status.namespace(&target.name, namespace_id))
+            })
+            .collect();
+        ClientStatus {
+            app_name: self.app_name.clone(),
+            context_path: state.env.uri.context_path.clone(),
+            host: state.host.clone(),
+            port: self.port,
+            uri_infos: self.uri_infos.clone(),
+            namespaces,
+        }
+    }
+
+    /// Check the connectivity with every admin server, in order: its url
+    /// parses, its host resolves, a TCP (and TLS) connection succeeds,
+    /// logging in at [`PLATFORM_LOGIN_SUFFIX`] works and each namespace
+    /// exists. Then check the advertised host is not a loopback address.
+    ///
+    /// The checks after a failed one are skipped for that server. Nothing
+    /// is registered.
+    #[must_use]
+    pub fn diagnose(&self) -> Diagnosis {
+        let state = self.state();
+        let mut checks = vec![];
+        for target in &state.targets {
+            let connect_ms = match target.register.timeout.connect_ms {
+                0 => 5_000,
+                connect_ms => connect_ms,
+            };
+            for server in &target.gateway_base_urls {
+                let (reach, reachable) = diagnose::reach(
+                    &target.name,
+                    server,
+                    &target.transport,
+                    Duration::from_millis(connect_ms),
+                );
+                checks.extend(reach);
+                let (authenticated, token) = if reachable {
+                    let (check, token) = target.diagnose_login(server);
+                    let authenticated = check.status != CheckStatus::Failed;
+                    checks.push(check);
+                    (authenticated, token)
+                } else {
+                    checks.push(
+                        Check::new(CheckKind::Authenticates, &target.name, server)
+                            .skipped("the admin server is unreachable"),
+                    );
+                    (false, None)
+                };
+                checks.extend(target.diagnose_namespaces(
+                    server,
+                    authenticated,
+                    token.as_deref(),
+                    self.admin_version(target, server),
+                ));
+            }
+        }
+        checks.push(diagnose::host(&state.host));
+        Diagnosis { checks }
+    }
+
+    /// The configuration currently in use.
+    #[must_use]
+    pub fn config(&self) -> ShenYuConfig {
+        self.state
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .env
+            .clone()
+    }
+
+    /// A copy of the state, so that no lock is held while calling shenyu
+    /// admin or the listeners, which may reload the client.
+    fn state(&self) -> ClientState {
+        self.state
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl ClientState {
+    fn new(env: ShenYuConfig) -> Result<Self, ShenYuError> {
+        env.validate()?;
+        let targets = env
+            .register_targets()
+            .into_iter()
+            .map(TargetState::new)
+            .collect::<Result<_, _>>()?;
+        let host = resolve_host(&env.uri)?;
+        Ok(ClientState { env, host, targets })
+    }
+
+    fn target(&self, name: &str) -> Option<&TargetState> {
+        self.targets.iter().find(|target| target.name == name)
+    }
+}
+
+impl TargetState {
+    fn new(target: RegisterTarget) -> Result<Self, ShenYuError> {
+        let namespace_ids: Vec<String> = target
             .register
             .namespace_id
             .clone()
@@ -104,12 +549,26 @@ impl ShenyuClient {
                 x.split(';').map(ToString::to_string).collect()
             });
 
-        let mut client = ShenyuClient {
-            headers,
-            app_name: app_name.to_string(),
-            env: config,
-            host: None,
-            port,
+        let transport = Transport::new(&target.register).map_err(|e| {
+            ShenYuError::config(format!("set up the transport of target {}", target.name), e)
+        })?;
+        let servers = servers::resolve(&target.register.servers, &transport).map_err(|e| {
+            ShenYuError::transport(format!("resolve the servers of target {}", target.name), e)
+        })?;
+        let admin_version = target
+            .register
+            .admin_version
+            .as_deref()
+            .map(str::parse)
+            .transpose()
+            .map_err(|e| {
+                ShenYuError::config(format!("admin_version of target {}", target.name), e)
+            })?;
+        let mut state = TargetState {
+            name: target.name,
+            register: target.register,
+            transport,
+            admin_version,
             namespace_ids,
             gateway_base_urls: vec![],
             register_meta_data_path_list: vec![],
@@ -117,24 +576,18 @@ impl ShenyuClient {
             register_token_servers: vec![],
             register_discover_config_servers: vec![],
             register_offline_servers: vec![],
-            uri_infos: uri_infos.to_owned(),
         };
-        client.set_up_gateway_service_url()?;
-        Ok(client)
+        state.set_up_gateway_service_url(servers)?;
+        Ok(state)
     }
-}
 
-impl ShenyuClient {
-    fn set_up_gateway_service_url(&mut self) -> Result<(), String> {
-        self.gateway_base_urls = self
-            .env
-            .register
-            .servers
-            .split(',')
-            .map(ToString::to_string)
-            .collect();
+    fn set_up_gateway_service_url(&mut self, servers: Vec<String>) -> Result<(), ShenYuError> {
+        self.gateway_base_urls = servers;
         if self.gateway_base_urls.is_empty() {
-            return Err(String::from("shenyu.register.servers is empty"));
+            return Err(ShenYuError::config(
+                format!("servers of target {}", self.name),
+                "no admin server is set",
+            ));
         }
 
         self.register_meta_data_path_list = self
@@ -162,345 +615,1704 @@ impl ShenyuClient {
             .iter()
             .map(|url| format!("{url}{REGISTER_OFFLINE_SUFFIX}"))
             .collect();
+        Ok(())
+    }
+
+    /// The headers configured for this target, the static token included.
+    fn static_headers(&self) -> impl Iterator<Item = (&str, &str)> {
+        let token = match &self.register.auth {
+            AuthConfig::Token { token, header } => Some((header.as_str(), token.expose())),
+            _ => None,
+        };
+        self.register
+            .headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.expose()))
+            .chain(token)
+    }
+
+    /// Check logging in to `server`, returning the token to use next, if
+    /// any.
+    fn diagnose_login(&self, server: &str) -> (Check, Option<String>) {
+        let check = Check::new(CheckKind::Authenticates, &self.name, server);
+        if self.register.auth != AuthConfig::Login {
+            return (check.skipped("register.auth is not login"), None);
+        }
+        let props = &self.register.props;
+        if props.username.is_empty() || props.password.is_empty() {
+            let check = check.failed(
+                "no username or password".to_string(),
+                "Set register.props.username and register.props.password".to_string(),
+            );
+            return (check, None);
+        }
+        let url = format!("{server}{PLATFORM_LOGIN_SUFFIX}");
+        let params = [
+            ("userName", props.username.as_str()),
+            ("password", props.password.expose()),
+        ];
+        match self.login(&url, &params) {
+            Ok(token) => {
+                let check = check.passed(format!("logged in as {}", props.username));
+                (check, Some(token))
+            }
+            Err(e) => {
+                let hint = if let ShenYuError::AdminRejection { .. } = e {
+                    "Check register.props.username and password match a shenyu admin user"
+                        .to_string()
+                } else {
+                    format!("Check {url} is the login endpoint of shenyu admin, not of a gateway or proxy")
+                };
+                // The error isn't shown, in case it carries the login query.
+                let detail = match e {
+                    ShenYuError::AdminRejection { status, .. } => {
+                        format!("{url} answered {status}")
+                    }
+                    e => format!("{}, login to {url}", e.kind()),
+                };
+                (check.failed(detail, hint), None)
+            }
+        }
+    }
+
+    /// Check each namespace exists on `server`, with the login `token`
+    /// when the target logs in. Skipped unless `authenticated`, and on
+    /// admins predating namespaces.
+    fn diagnose_namespaces(
+        &self,
+        server: &str,
+        authenticated: bool,
+        token: Option<&str>,
+        version: AdminVersion,
+    ) -> Vec<Check> {
+        let check = |namespace_id: &str| Check {
+            namespace_id: Some(namespace_id.to_string()),
+            ..Check::new(CheckKind::NamespaceExists, &self.name, server)
+        };
+        if !version.supports_namespaces() {
+            let detail = format!("shenyu admin {version} predates namespaces");
+            return self
+                .namespace_ids
+                .iter()
+                .map(|namespace_id| check(namespace_id).skipped(&detail))
+                .collect();
+        }
+        if !authenticated {
+            return self
+                .namespace_ids
+                .iter()
+                .map(|namespace_id| check(namespace_id).skipped("an earlier check failed"))
+                .collect();
+        }
+        let url = format!("{server}{NAMESPACE_LIST_SUFFIX}");
+        let namespaces = self.namespaces(&url, token);
+        self.namespace_ids
+            .iter()
+            .map(|namespace_id| match &namespaces {
+                Ok(namespaces) if namespaces.contains(namespace_id) => {
+                    check(namespace_id).passed("found".to_string())
+                }
+                Ok(namespaces) => check(namespace_id).failed(
+                    format!("not among {namespaces:?}"),
+                    format!(
+                        "Create namespace {namespace_id} in shenyu admin, or fix \
+                         register.namespace_id"
+                    ),
+                ),
+                Err(e) => check(namespace_id).failed(
+                    e.to_string(),
+                    "Admins before 2.7.0 have no namespaces, check the admin version and \
+                     that the user may list namespaces"
+                        .to_string(),
+                ),
+            })
+            .collect()
+    }
+
+    /// The ids of the namespaces listed at `url`.
+    fn namespaces(&self, url: &str, token: Option<&str>) -> Result<Vec<String>, ShenYuError> {
+        let response = self.get(url, token)?;
+        let status = response.status();
+        let res_data: Value = response
+            .into_json()
+            .map_err(|e| ShenYuError::serialization(format!("read the answer of {url}"), e))?;
+        match res_data.get("data").and_then(Value::as_array) {
+            Some(namespaces) => Ok(namespaces
+                .iter()
+                .filter_map(|namespace| namespace.get("namespaceId")?.as_str())
+                .map(ToString::to_string)
+                .collect()),
+            None => Err(ShenYuError::AdminRejection {
+                url: url.to_string(),
+                status,
+                message: res_data.to_string(),
+            }),
+        }
+    }
+
+    /// The release of the admin at `server`, from the endpoints it lacks.
+    ///
+    /// An endpoint answering 2xx or 405 exists and one answering 404
+    /// doesn't. Any other answer, such as 401 or 503, is an error, leaving
+    /// the release undetected.
+    fn detect_admin_version(
+        &self,
+        server: &str,
+        token: Option<&str>,
+    ) -> Result<AdminVersion, ShenYuError> {
+        let has = |suffix: &str| -> Result<bool, ShenYuError> {
+            let url = format!("{server}{suffix}");
+            let response = self.get(&url, token)?;
+            match response.status() {
+                200..=299 | 405 => Ok(true),
+                404 => Ok(false),
+                status => Err(ShenYuError::AdminRejection {
+                    url,
+                    status,
+                    message: response.into_string().unwrap_or_default(),
+                }),
+            }
+        };
+        if has(NAMESPACE_LIST_SUFFIX)? {
+            Ok(AdminVersion::NAMESPACES)
+        } else if has(REGISTER_DISCOVERY_CONFIG_SUFFIX)? {
+            Ok(AdminVersion::DISCOVERY_CONFIG)
+        } else {
+            Ok(AdminVersion::LEGACY)
+        }
+    }
 
-        #[allow(unused_assignments)]
-        let mut host = None;
-        #[cfg(not(target_os = "macos"))]
+    /// Get `url` with the headers of this target and `token`, whatever the
+    /// status of the answer.
+    fn get(&self, url: &str, token: Option<&str>) -> Result<ureq::Response, ShenYuError> {
+        let mut request = self.transport.agent(url).get(url);
+        for (name, value) in self.static_headers() {
+            request = request.set(name, value);
+        }
+        if let Some(token) = token {
+            request = request.set(TOKEN_HEADER, token);
+        }
+        request
+            .call()
+            .or_any_status()
+            .map_err(|e| transport::error(url, e))
+    }
+
+    /// Log in to the admin server `url`, returning its token.
+    fn login(&self, url: &str, params: &[(&str, &str)]) -> Result<String, ShenYuError> {
+        let mut request = self
+            .transport
+            .agent(url)
+            .get(url)
+            .query_pairs(params.iter().copied());
+        for (name, value) in self.static_headers() {
+            request = request.set(name, value);
+        }
+        let response = request
+            .call()
+            .or_any_status()
+            .map_err(|e| transport::error(url, e))?;
+        let status = response.status();
+        let res_data: Value = response
+            .into_json()
+            .map_err(|e| ShenYuError::serialization(format!("read the answer of {url}"), e))?;
+        if let Some(token) = res_data
+            .get("data")
+            .and_then(|data| data.get("token"))
+            .and_then(|token| token.as_str())
         {
-            host = match local_ip_address::local_ip() {
-                Ok(IpAddr::V4(ipv4)) => Some(IpAddr::V4(ipv4)),
-                Ok(IpAddr::V6(ipv6)) => Some(IpAddr::from(ipv6.to_ipv4().unwrap())),
-                _ => None,
-            };
+            return Ok(token.to_string());
         }
-        #[cfg(target_os = "macos")]
+        Err(ShenYuError::AdminRejection {
+            url: url.to_string(),
+            status,
+            message: res_data.to_string(),
+        })
+    }
+}
+
+impl Debug for ShenyuClient {
+    /// Header values and tokens are redacted.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let headers: BTreeMap<String, &str> = self
+            .headers
+            .iter()
+            .map(|header| (header.key().clone(), REDACTED))
+            .collect();
+        let tokens: BTreeMap<String, &str> = self
+            .tokens
+            .iter()
+            .map(|token| (token.key().clone(), REDACTED))
+            .collect();
+        let mut debug = f.debug_struct("ShenyuClient");
+        _ = debug
+            .field("headers", &headers)
+            .field("tokens", &tokens)
+            .field("app_name", &self.app_name)
+            .field("port", &self.port)
+            .field("uri_infos", &self.uri_infos)
+            .field("state", &self.state)
+            .field("listeners", &self.listeners.len())
+            .field("lost_heartbeats", &self.lost_heartbeats)
+            .field("offline", &self.offline)
+            .field("status", &self.status)
+            .field("journal", &self.journal)
+            .field("admin_versions", &self.admin_versions);
+        #[cfg(feature = "metrics")]
         {
-            use local_ip_address::macos;
-            for (_, ipaddr) in macos::list_afinet_netifas().unwrap() {
-                if IpAddr::V4(std::net::Ipv4Addr::new(127, 0, 0, 1)).eq(&ipaddr) {
-                    continue;
+            _ = debug.field("metrics", &self.metrics);
+        }
+        debug.finish()
+    }
+}
+
+impl RegistrationDiff {
+    fn between(old: &ClientState, new: &ClientState) -> Self {
+        let mut diff = RegistrationDiff::default();
+        for old_target in &old.targets {
+            let Some(new_target) = new.target(&old_target.name) else {
+                diff.removed_targets.push(old_target.name.clone());
+                continue;
+            };
+            for server in &old_target.gateway_base_urls {
+                if !new_target.gateway_base_urls.contains(server)
+                    && !diff.removed_servers.contains(server)
+                {
+                    diff.removed_servers.push(server.clone());
+                }
+            }
+            for namespace in &old_target.namespace_ids {
+                if !new_target.namespace_ids.contains(namespace)
+                    && !diff.removed_namespaces.contains(namespace)
+                {
+                    diff.removed_namespaces.push(namespace.clone());
                 }
-                host = match ipaddr {
-                    IpAddr::V4(ipv4) => Some(IpAddr::from(ipv4)),
-                    IpAddr::V6(_) => continue,
-                };
             }
         }
-        if let Some(host) = host {
-            self.host = Some(host.to_string());
-            Ok(())
-        } else {
-            Err("Failed to determine local IP address".to_string())
+        diff.uri_changed = old.host != new.host
+            || old.env.uri.context_path != new.env.uri.context_path
+            || old.env.uri.rpc_type != new.env.uri.rpc_type;
+        let targets = |state: &ClientState| -> Vec<(String, RegisterConfig)> {
+            state
+                .targets
+                .iter()
+                .map(|target| (target.name.clone(), target.register.clone()))
+                .collect()
+        };
+        diff.changed = diff.uri_changed
+            || targets(old) != targets(new)
+            || old.env.discovery != new.env.discovery;
+        diff
+    }
+}
+
+impl ShenyuClient {
+    fn register_state(&self, state: &ClientState) -> RegistrationReport {
+        let targets = state
+            .targets
+            .iter()
+            .map(|target| self.register_target(state, target))
+            .collect();
+        RegistrationReport { targets }
+    }
+
+    fn register_target(&self, state: &ClientState, target: &TargetState) -> TargetReport {
+        let _span = self.target_span(target).entered();
+        let mut report = TargetReport::new(&target.name);
+        for namespace_id in &target.namespace_ids {
+            self.status.begin(&target.name, namespace_id);
+        }
+        match self.authenticate(target) {
+            Ok(Some(token)) => {
+                info!("got register token");
+                _ = self.tokens.insert(target.name.clone(), token);
+            }
+            Ok(None) => _ = self.tokens.remove(&target.name),
+            Err(e) => {
+                error!(kind = e.kind(), "failed to get register token");
+                report.failures.push(format!("login: {e}"));
+            }
+        }
+        self.detect_admin_versions(target);
+        self.register_all_metadata_in_target(state, target, true, &mut report);
+        self.register_uri_in_target(state, target, &mut report);
+        self.register_discovery_config_in_target(state, target, &mut report);
+        for namespace_id in &target.namespace_ids {
+            self.status.finish(&target.name, namespace_id);
         }
+        info!(
+            succeeded = report.succeeded,
+            failed = report.failures.len(),
+            "registration finished"
+        );
+        report
     }
 
-    fn request(&self, url: &str, json_data: &Value) -> Result<bool, Error> {
-        let mut builder = ureq::post(url);
+    /// The span of a run against `target`, parent of its namespace and
+    /// admin call spans.
+    fn target_span(&self, target: &TargetState) -> Span {
+        info_span!("shenyu.registration", app = %self.app_name, target = %target.name)
+    }
+
+    /// Authenticate with `target` as configured by `auth`, returning the
+    /// token to send as `X-Access-Token` when logging in.
+    fn authenticate(&self, target: &TargetState) -> Result<Option<String>, ShenYuError> {
+        match target.register.auth {
+            AuthConfig::Login => self.get_register_token(target).map(Some),
+            AuthConfig::Token { .. } | AuthConfig::Headers | AuthConfig::None => Ok(None),
+        }
+    }
+
+    fn get_register_token(&self, target: &TargetState) -> Result<String, ShenYuError> {
+        let props = &target.register.props;
+        if props.username.is_empty() || props.password.is_empty() {
+            return Err(ShenYuError::authentication(
+                format!("login to target {}", target.name),
+                "props.username and password are required",
+            ));
+        }
+        let params = [
+            ("userName", props.username.as_str()),
+            ("password", props.password.expose()),
+        ];
+
+        let call = Call::new(Operation::Login, "");
+        let _span = call.span().entered();
+        let mut result = Err(ShenYuError::authentication(
+            format!("login to target {}", target.name),
+            "no admin server is set",
+        ));
+        let login = Login {
+            username: props.username.clone(),
+        };
+        for (attempt, url) in target.register_token_servers.iter().enumerate() {
+            let span = admin_span(url, attempt + 1);
+            let _entered = span.enter();
+            let started = Instant::now();
+            let token = target.login(url, &params);
+            self.finish_call(&call, &span, url, started, token.as_ref().err());
+            let event = AdminEvent {
+                target: &target.name,
+                server: admin_server(url),
+                payload: &login,
+            };
+            match token {
+                Ok(token) => {
+                    self.notify(|listener| listener.on_token_acquired(&event));
+                    return Ok(token);
+                }
+                Err(e) => {
+                    warn!(server = admin_server(url), kind = e.kind(), "login failed");
+                    self.notify(|listener| listener.on_token_failed(&event, &e));
+                    result = Err(e);
+                }
+            }
+        }
+        result.map_err(|e| match e {
+            ShenYuError::AdminRejection { .. } => {
+                ShenYuError::authentication(format!("login to target {}", target.name), e)
+            }
+            e => e,
+        })
+    }
+
+    /// Run `f` on every target, collecting a report per target.
+    fn each_target<F>(&self, f: F) -> RegistrationReport
+    where
+        F: Fn(&ClientState, &TargetState, &mut TargetReport),
+    {
+        let state = self.state();
+        let targets = state
+            .targets
+            .iter()
+            .map(|target| {
+                let _span = self.target_span(target).entered();
+                let mut report = TargetReport::new(&target.name);
+                f(&state, target, &mut report);
+                report
+            })
+            .collect();
+        RegistrationReport { targets }
+    }
+
+    fn request<P: Serialize>(
+        &self,
+        target: &TargetState,
+        url: &str,
+        payload: &P,
+    ) -> Result<(), ShenYuError> {
+        let mut builder = target.transport.agent(url).post(url);
         // 遍历header， 添加到builder中
         for r in &self.headers {
             builder = builder.set(r.key(), r.value());
         }
-        let res = builder.send_json(json_data).map_err(|e| {
-            Error::new(ErrorKind::Other, format!("request {url} failed, cause {e}"))
-        })?;
+        for (name, value) in target.static_headers() {
+            builder = builder.set(name, value);
+        }
+        if let Some(token) = self.tokens.get(&target.name) {
+            builder = builder.set(TOKEN_HEADER, token.value());
+        }
+        let res = builder
+            .send_json(payload)
+            .or_any_status()
+            .map_err(|e| transport::error(url, e))?;
         let status_code = res.status();
-        let msg = res.into_string()?;
+        let msg = res
+            .into_string()
+            .map_err(|e| ShenYuError::transport(format!("read the answer of {url}"), e))?;
 
         if msg == "success" {
-            Ok(true)
+            Ok(())
         } else {
-            warn!(
-                "Request ({}) failed, status code: {}, msg: {}",
-                url, status_code, msg
-            );
-            Ok(false)
+            Err(ShenYuError::AdminRejection {
+                url: url.to_string(),
+                status: status_code,
+                message: msg,
+            })
         }
     }
 
-    pub(crate) fn get_register_token(&self) -> Result<String, Error> {
-        let hashmap = &self.env.register.props;
-        let params = [
-            ("userName", hashmap.get("username").unwrap().as_str()),
-            ("password", hashmap.get("password").unwrap().as_str()),
-        ];
-
-        let result = Err(ShenYuError::new(500, "Can't get register token".to_string()).into());
-        for url in &self.register_token_servers {
-            let res_data: Value = ureq::get(url)
-                .query_pairs(params)
-                .call()
-                .or_any_status()
-                .map_err(|e| Error::new(ErrorKind::Other, format!("{e}")))?
-                .into_json()?;
-            match res_data
-                .get("data")
-                .and_then(|data| data.get("token"))
-                .and_then(|token| token.as_str())
-            {
-                Some(token) => return Ok(token.to_string()),
-                None => continue,
+    /// Send `payload` to `url` for `call`, recording the outcome in
+    /// `report`.
+    fn send<P: Serialize + Debug>(
+        &self,
+        target: &TargetState,
+        call: &Call<'_>,
+        url: &str,
+        payload: &P,
+        report: &mut TargetReport,
+    ) -> Result<(), ShenYuError> {
+        let span = admin_span(url, 1);
+        let _entered = span.enter();
+        debug!(?payload, "sending to shenyu admin");
+        let started = Instant::now();
+        let result = self.request(target, url, payload);
+        self.finish_call(call, &span, url, started, result.as_ref().err());
+        self.status.record(
+            &target.name,
+            admin_server(url),
+            call,
+            result.as_ref().copied(),
+        );
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            let server = admin_server(url);
+            let routes = self
+                .status
+                .registered_routes(&target.name, server, call.namespace);
+            metrics.set_routes(server, call.namespace, routes);
+        }
+        self.audit(target, call, url, result.as_ref().err());
+        match &result {
+            Ok(()) => {
+                info!("shenyu admin call succeeded");
+                report.succeeded += 1;
+            }
+            Err(e) => {
+                error!(error = %e, "shenyu admin call failed");
+                report.failures.push(e.to_string());
             }
         }
         result
     }
 
-    /// Register uri.
-    pub fn register_uri(&self) {
-        let app_name = &self.app_name.clone();
-        let rpc_type = &self.env.uri.rpc_type.clone();
-        let context_path = &self.env.uri.context_path.clone();
-        let namespace_ids = &self.namespace_ids.clone();
-        namespace_ids.iter().for_each(|namespace_id| {
-            self._register_uri(app_name, rpc_type, context_path, namespace_id);
-        });
+    /// Detect the release of the admin servers of `target` not known yet,
+    /// unless it is configured.
+    fn detect_admin_versions(&self, target: &TargetState) {
+        if target.admin_version.is_some() {
+            return;
+        }
+        let token = self.tokens.get(&target.name).map(|token| token.clone());
+        for server in &target.gateway_base_urls {
+            if self.admin_versions.contains_key(server) {
+                continue;
+            }
+            match target.detect_admin_version(server, token.as_deref()) {
+                Ok(version) => {
+                    info!(server, %version, "detected the shenyu admin version");
+                    if !version.supports_namespaces() && target.namespace_ids.len() > 1 {
+                        warn!(
+                            server,
+                            "shenyu admin {version} predates namespaces, only {} is registered",
+                            target.namespace_ids[0]
+                        );
+                    }
+                    _ = self.admin_versions.insert(server.clone(), version);
+                }
+                Err(e) => debug!(error = %e, server, "failed to detect the shenyu admin version"),
+            }
+        }
+    }
+
+    /// The release of the admin at `url`: configured, detected, or else
+    /// assumed to have namespaces.
+    fn admin_version(&self, target: &TargetState, url: &str) -> AdminVersion {
+        target
+            .admin_version
+            .or_else(|| {
+                self.admin_versions
+                    .get(admin_server(url))
+                    .map(|version| *version)
+            })
+            .unwrap_or(AdminVersion::NAMESPACES)
     }
 
-    fn _register_uri(
+    /// `payload` as the admin at `url` takes it, `None` when it isn't sent
+    /// there: admins predating namespaces get it once, for the first
+    /// namespace, without `namespaceId`.
+    fn for_admin<'p, P: Namespaced>(
         &self,
-        app_name: &str,
-        rpc_type: &str,
-        context_path: &str,
+        target: &TargetState,
+        url: &str,
         namespace_id: &str,
+        payload: &'p P,
+    ) -> Option<Cow<'p, P>> {
+        if self.admin_version(target, url).supports_namespaces() {
+            return Some(Cow::Borrowed(payload));
+        }
+        (target.namespace_ids.first().map(String::as_str) == Some(namespace_id))
+            .then(|| Cow::Owned(payload.without_namespace()))
+    }
+
+    /// Call `f` on every listener.
+    fn notify<F: Fn(&dyn RegistrationListener)>(&self, f: F) {
+        for listener in &self.listeners {
+            f(listener.as_ref());
+        }
+    }
+
+    /// Write a call to the admin endpoint `url` to the journal, if any.
+    fn audit(&self, target: &TargetState, call: &Call<'_>, url: &str, error: Option<&ShenYuError>) {
+        let Some(journal) = &self.journal else {
+            return;
+        };
+        let event_type = match call.operation {
+            Operation::Offline => EventType::OFFLINE,
+            Operation::Heartbeat => EventType::UPDATED,
+            _ => EventType::REGISTER,
+        };
+        let record = AuditRecord {
+            route: call.route.map(str::to_string),
+            result: outcome(error).to_string(),
+            error: error.map(ToString::to_string),
+            ..AuditRecord::now(
+                &target.name,
+                admin_server(url),
+                call.namespace,
+                event_type,
+                call.operation.as_str(),
+            )
+        };
+        if let Err(e) = journal.append(&record) {
+            warn!(error = %e, "failed to write the audit journal");
+        }
+    }
+
+    /// Record the latency and outcome of a call to the admin endpoint
+    /// `url`, started at `started`, on its span and in the metrics.
+    #[cfg_attr(not(feature = "metrics"), allow(unused_variables, clippy::unused_self))]
+    fn finish_call(
+        &self,
+        call: &Call<'_>,
+        span: &Span,
+        url: &str,
+        started: Instant,
+        error: Option<&ShenYuError>,
     ) {
-        let port = &self.port;
-        let host = &self.host;
-
-        let json_data = serde_json::json!({
-            "appName": app_name,
-            "contextPath": context_path,
-            "protocol": rpc_type,
-            "rpcType": rpc_type,
-            "host": host.clone().unwrap(),
-            "port": port,
-            "namespaceId": namespace_id,
-            "eventType": EventType::REGISTER.to_string(),
-        });
+        let latency = started.elapsed();
+        let outcome = outcome(error);
+        _ = span.record(
+            "latency_ms",
+            u64::try_from(latency.as_millis()).unwrap_or(u64::MAX),
+        );
+        _ = span.record("outcome", outcome);
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.observe(call, admin_server(url), latency, outcome);
+        }
+    }
+
+    /// Register uri.
+    pub fn register_uri(&self) -> RegistrationReport {
+        self.each_target(|state, target, report| {
+            self.register_uri_in_target(state, target, report);
+        })
+    }
+
+    fn register_uri_in_target(
+        &self,
+        state: &ClientState,
+        target: &TargetState,
+        report: &mut TargetReport,
+    ) {
+        for namespace_id in &target.namespace_ids {
+            let call = Call::new(Operation::RegisterUri, namespace_id);
+            self.register_uri_in_namespace(state, target, &call, report);
+        }
+    }
+
+    /// Register the uri in the namespace of `call`, for a registration or
+    /// a heartbeat.
+    fn register_uri_in_namespace(
+        &self,
+        state: &ClientState,
+        target: &TargetState,
+        call: &Call<'_>,
+        report: &mut TargetReport,
+    ) {
+        let payload = UriRegistration {
+            rpc_type: Some(state.env.uri.rpc_type.clone()),
+            ..self.uri_registration(state, call.namespace, EventType::REGISTER)
+        };
 
         // Broadcast to all shenyu admin.
-        for url in &self.register_uri_list {
-            if let Ok(true) = self.request(url, &json_data) {
-                info!(
-                    "[SUCCESS], register uri success, register data: {:#?}",
-                    json_data
-                );
+        let _span = call.span().entered();
+        for url in &target.register_uri_list {
+            let Some(sent) = self.for_admin(target, url, call.namespace, &payload) else {
                 continue;
+            };
+            let payload = &*sent;
+            let result = self.send(target, call, url, payload, report);
+            let event = AdminEvent {
+                target: &target.name,
+                server: admin_server(url),
+                payload,
+            };
+            if call.operation == Operation::Heartbeat {
+                self.heartbeat_result(&event, call.namespace, result.as_ref().err());
+            } else if result.is_ok() {
+                self.notify(|listener| listener.on_uri_registered(&event));
             }
-            error!(
-                "[ERROR], register uri to {} failed, app_name: {}, host: {}, port: {}",
-                url,
-                app_name,
-                host.clone().unwrap(),
-                port
-            );
         }
     }
 
-    /// Register metadata.
-    pub fn register_all_metadata(&self, enabled: bool) {
-        for x in &self.uri_infos {
-            self.register_metadata(
-                false,
-                Some(&x.path),
-                Some(&x.method_name),
-                Some(&x.rule_name),
-                enabled,
-            );
+    /// The uri of the instance in `namespace_id`, without its rpc type.
+    fn uri_registration(
+        &self,
+        state: &ClientState,
+        namespace_id: &str,
+        event_type: EventType,
+    ) -> UriRegistration {
+        UriRegistration {
+            app_name: self.app_name.clone(),
+            context_path: state.env.uri.context_path.clone(),
+            protocol: state.env.uri.rpc_type.clone(),
+            rpc_type: None,
+            host: state.host.clone(),
+            port: self.port,
+            namespace_id: Some(namespace_id.to_string()),
+            event_type,
         }
     }
 
-    fn register_metadata(
+    /// Tell the listeners when heartbeats to a server start failing, or
+    /// succeed again.
+    fn heartbeat_result(
         &self,
-        register_all: bool,
-        path: Option<&str>,
-        method: Option<&str>,
-        rule_name: Option<&str>,
-        enabled: bool,
+        event: &AdminEvent<'_, UriRegistration>,
+        namespace_id: &str,
+        error: Option<&ShenYuError>,
     ) {
-        let context_path = &self.env.uri.context_path.clone();
-        let app_name = &self.app_name.clone();
-        let namespace_ids = &self.namespace_ids.clone();
-        let rpc_type = &self.env.uri.rpc_type.clone();
-        let path = if register_all {
-            format!("{context_path}**")
-        } else {
-            path.unwrap_or("").to_string()
-        };
+        let key = (
+            event.target.to_string(),
+            event.server.to_string(),
+            namespace_id.to_string(),
+        );
+        match error {
+            Some(e) => {
+                if self.lost_heartbeats.insert(key) {
+                    self.notify(|listener| listener.on_heartbeat_lost(event, e));
+                }
+            }
+            None => {
+                if self.lost_heartbeats.remove(&key).is_some() {
+                    self.notify(|listener| listener.on_heartbeat_recovered(event));
+                }
+            }
+        }
+    }
 
-        let rule_name = rule_name.unwrap_or(&path).to_string();
-        namespace_ids.iter().for_each(|namespace_id| {
-            self._register_metadata(
-                app_name,
-                rpc_type,
-                context_path.to_string(),
-                path.as_str(),
-                method,
-                rule_name.clone(),
-                namespace_id,
+    /// Send the uri registration again to every admin server, so they
+    /// keep the instance alive.
+    ///
+    /// Nothing is sent after [`ShenyuClient::offline_register`], until the
+    /// client registers again. See [`crate::watch::Heartbeat`] to send it
+    /// periodically.
+    pub fn heartbeat(&self) -> RegistrationReport {
+        if self.offline.load(Ordering::Acquire) {
+            return RegistrationReport::default();
+        }
+        self.each_target(|state, target, report| {
+            for namespace_id in &target.namespace_ids {
+                let call = Call::new(Operation::Heartbeat, namespace_id);
+                self.register_uri_in_namespace(state, target, &call, report);
+            }
+        })
+    }
+
+    /// Register metadata.
+    pub fn register_all_metadata(&self, enabled: bool) -> RegistrationReport {
+        self.each_target(|state, target, report| {
+            self.register_all_metadata_in_target(state, target, enabled, report);
+        })
+    }
+
+    fn register_all_metadata_in_target(
+        &self,
+        state: &ClientState,
+        target: &TargetState,
+        enabled: bool,
+        report: &mut TargetReport,
+    ) {
+        for x in &self.uri_infos {
+            let metadata = Metadata {
+                path: &x.path,
+                method: Some(&x.method_name),
+                rule_name: &x.rule_name,
                 enabled,
-            );
-        });
+            };
+            for namespace_id in &target.namespace_ids {
+                self.register_metadata_in_namespace(state, target, &metadata, namespace_id, report);
+            }
+        }
     }
 
-    fn _register_metadata(
+    fn register_metadata_in_namespace(
         &self,
-        app_name: &str,
-        rpc_type: &str,
-        context_path: String,
-        path: &str,
-        method: Option<&str>,
-        rule_name: String,
+        state: &ClientState,
+        target: &TargetState,
+        metadata: &Metadata<'_>,
         namespace_id: &str,
-        enabled: bool,
+        report: &mut TargetReport,
     ) {
-        let json_data = serde_json::json!({
-            "appName": app_name,
-            "contextPath": context_path.clone(),
-            "path": context_path.clone() + path,
-            "pathDesc": "",
-            "rpcType": rpc_type,
-            "ruleName": context_path.clone() + rule_name.as_str(),
-            "serviceName": app_name,
-            "methodName": method.unwrap_or("").to_string(),
-            "parameterTypes": "",
-            "rpcExt": "",
-            "host": self.host.clone().unwrap(),
-            "port": self.port,
-            "namespaceId": namespace_id,
-            "enabled": enabled,
-            "registerMetaData": "",
-            "pluginNames": []
-        });
+        let context_path = &state.env.uri.context_path;
+        let path = metadata.path;
+        let payload = MetadataRegistration {
+            app_name: self.app_name.clone(),
+            context_path: context_path.clone(),
+            path: format!("{context_path}{path}"),
+            path_desc: String::new(),
+            rpc_type: state.env.uri.rpc_type.clone(),
+            rule_name: format!("{context_path}{}", metadata.rule_name),
+            service_name: self.app_name.clone(),
+            method_name: metadata.method.unwrap_or("").to_string(),
+            parameter_types: String::new(),
+            rpc_ext: String::new(),
+            host: state.host.clone(),
+            port: self.port,
+            namespace_id: Some(namespace_id.to_string()),
+            enabled: metadata.enabled,
+            register_meta_data: String::new(),
+            plugin_names: vec![],
+        };
 
-        for url in &self.register_meta_data_path_list {
-            if let Ok(true) = self.request(url, &json_data) {
-                info!(
-                    "[SUCCESS], register metadata success, register data: {:#?}",
-                    &json_data
-                );
+        let call = Call {
+            route: Some(path),
+            ..Call::new(Operation::RegisterMetadata, namespace_id)
+        };
+        let _span = call.span().entered();
+        for url in &target.register_meta_data_path_list {
+            let Some(sent) = self.for_admin(target, url, namespace_id, &payload) else {
                 continue;
+            };
+            let payload = &*sent;
+            let result = self.send(target, &call, url, payload, report);
+            let event = AdminEvent {
+                target: &target.name,
+                server: admin_server(url),
+                payload,
+            };
+            match &result {
+                Ok(()) => self.notify(|listener| listener.on_route_registered(&event)),
+                Err(e) => self.notify(|listener| listener.on_route_failed(&event, e)),
             }
-            error!(
-                "[ERROR], register metadata to {} failed, app_name: {}, path: {}, contextPath: {}",
-                url, app_name, path, context_path
-            );
         }
     }
 
     /// Register discovery config.
-    pub fn register_discovery_config(&self) {
-        let discovery_type = &self.env.discovery.discovery_type.clone();
-        let register_path = &self.env.discovery.register_path.clone();
-        let server_lists = &self.env.discovery.server_lists.clone();
-        let props = &self.env.discovery.props.clone();
-        let plugin_name = &self.env.discovery.plugin_name.clone();
-        let context_path = &self.env.uri.context_path.clone();
-        let namespace_ids = &self.namespace_ids.clone();
-
-        let port = &self.port;
-        let host = &self.host;
-        namespace_ids.iter().for_each(|namespace_id| {
-            self._register_discovery_config(
-                discovery_type,
-                register_path,
-                server_lists,
-                props,
-                plugin_name,
-                context_path,
-                namespace_id,
-                host,
-                port,
-            );
-        });
+    pub fn register_discovery_config(&self) -> RegistrationReport {
+        self.each_target(|state, target, report| {
+            self.register_discovery_config_in_target(state, target, report);
+        })
     }
 
-    fn _register_discovery_config(
+    fn register_discovery_config_in_target(
         &self,
-        discovery_type: &str,
-        register_path: &str,
-        server_lists: &str,
-        props: &HashMap<String, String>,
-        plugin_name: &str,
-        context_path: &str,
+        state: &ClientState,
+        target: &TargetState,
+        report: &mut TargetReport,
+    ) {
+        for namespace_id in &target.namespace_ids {
+            self.register_discovery_config_in_namespace(state, target, namespace_id, report);
+        }
+    }
+
+    fn register_discovery_config_in_namespace(
+        &self,
+        state: &ClientState,
+        target: &TargetState,
         namespace_id: &str,
-        host: &Option<String>,
-        port: &u16,
+        report: &mut TargetReport,
     ) {
-        let json_data = serde_json::json!({
-            "name": "default".to_string() + discovery_type,
-            "selectorName": context_path,
-            "handler": "{}",
-            "listenerNode":register_path,
-            "serverList": server_lists,
-            "props": props,
-            "discoveryType": discovery_type,
-            "pluginName": plugin_name,
-            "namespaceId": namespace_id,
-        });
+        let discovery = &state.env.discovery;
+        let discovery_type = &discovery.discovery_type;
+        let context_path = &state.env.uri.context_path;
+
+        let payload = DiscoveryConfigRegistration {
+            name: format!("default{discovery_type}"),
+            selector_name: context_path.clone(),
+            handler: "{}".to_string(),
+            listener_node: discovery.register_path.clone(),
+            server_list: discovery.server_lists.clone(),
+            props: discovery.props.clone(),
+            discovery_type: discovery_type.clone(),
+            plugin_name: discovery.plugin_name.clone(),
+            namespace_id: Some(namespace_id.to_string()),
+        };
 
         // Broadcast to all shenyu admin.
-        for url in &self.register_discover_config_servers {
-            if let Ok(true) = self.request(url, &json_data) {
-                info!(
-                    "[SUCCESS], register discover config success, register data: {:#?}",
-                    &json_data
-                );
+        let call = Call::new(Operation::RegisterDiscoveryConfig, namespace_id);
+        let _span = call.span().entered();
+        for url in &target.register_discover_config_servers {
+            let version = self.admin_version(target, url);
+            if !version.supports_discovery_config() {
+                debug!(url, %version, "shenyu admin predates discovery configs");
                 continue;
             }
-            error!(
-                "[ERROR], register discover config to {} failed, discovery_type: {}, host: {}, port: {}",
-                url, discovery_type, host.clone().unwrap(), port
-            );
+            let Some(sent) = self.for_admin(target, url, namespace_id, &payload) else {
+                continue;
+            };
+            let payload = &*sent;
+            if self.send(target, &call, url, payload, report).is_ok() {
+                let event = AdminEvent {
+                    target: &target.name,
+                    server: admin_server(url),
+                    payload,
+                };
+                self.notify(|listener| listener.on_discovery_registered(&event));
+            }
         }
     }
 
     /// Offline from shenyu.
-    pub fn offline_register(&self) {
-        let app_name = &self.app_name.clone();
-        let namespace_ids = &self.namespace_ids.clone();
-        let rpc_type = &self.env.uri.rpc_type.clone();
-        let context_path = &self.env.uri.context_path.clone();
-
-        let port = &self.port;
-        let host = &self.host;
-        namespace_ids.iter().for_each(|namespace_id| {
-            self._offline_register(app_name, rpc_type, context_path, namespace_id, port, host);
-        });
+    pub fn offline_register(&self) -> RegistrationReport {
+        self.offline.store(true, Ordering::Release);
+        self.each_target(|state, target, report| {
+            for namespace_id in &target.namespace_ids {
+                self.offline_register_in_namespace(
+                    state,
+                    target,
+                    &target.register_offline_servers,
+                    namespace_id,
+                    report,
+                );
+            }
+        })
     }
-    fn _offline_register(
+
+    /// Offline `state`'s registration from the given admin servers and
+    /// namespaces of `target`.
+    fn offline_state(
         &self,
-        app_name: &str,
-        rpc_type: &str,
-        context_path: &str,
+        state: &ClientState,
+        target: &TargetState,
+        servers: &[String],
+        namespace_ids: &[String],
+        report: &mut TargetReport,
+    ) {
+        let offline_servers: Vec<String> = servers
+            .iter()
+            .map(|url| format!("{url}{REGISTER_OFFLINE_SUFFIX}"))
+            .collect();
+        for namespace_id in namespace_ids {
+            self.offline_register_in_namespace(
+                state,
+                target,
+                &offline_servers,
+                namespace_id,
+                report,
+            );
+        }
+    }
+
+    fn offline_register_in_namespace(
+        &self,
+        state: &ClientState,
+        target: &TargetState,
+        offline_servers: &[String],
         namespace_id: &str,
-        port: &u16,
-        host: &Option<String>,
+        report: &mut TargetReport,
     ) {
-        let json_data = serde_json::json!({
-            "appName": app_name,
-            "contextPath": context_path,
-            "protocol": rpc_type,
-            "host": host.clone().unwrap(),
-            "port": port,
-            "namespaceId": namespace_id,
-            "eventType": EventType::OFFLINE.to_string(),
-        });
+        let payload = self.uri_registration(state, namespace_id, EventType::OFFLINE);
 
         // Broadcast offline to all shenyu admin.
-        for url in &self.register_offline_servers {
-            if let Ok(true) = self.request(url, &json_data) {
-                info!(
-                    "[SUCCESS], offline success, register data: {:#?}",
-                    &json_data
-                );
+        let call = Call::new(Operation::Offline, namespace_id);
+        let _span = call.span().entered();
+        for url in offline_servers {
+            let Some(sent) = self.for_admin(target, url, namespace_id, &payload) else {
                 continue;
-            }
-            error!(
-                "[ERROR], offline from {} failed, app_name: {}, host: {}, port: {}",
-                url,
-                app_name,
-                host.clone().unwrap(),
-                port
+            };
+            let payload = &*sent;
+            let result = self.send(target, &call, url, payload, report);
+            let event = AdminEvent {
+                target: &target.name,
+                server: admin_server(url),
+                payload,
+            };
+            self.notify(|listener| {
+                listener.on_offline_completed(&event, result.as_ref().copied());
+            });
+        }
+    }
+}
+
+/// What a request to shenyu admin is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Operation {
+    Login,
+    RegisterUri,
+    RegisterMetadata,
+    RegisterDiscoveryConfig,
+    Offline,
+    Heartbeat,
+}
+
+impl Operation {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Operation::Login => "login",
+            Operation::RegisterUri => "register_uri",
+            Operation::RegisterMetadata => "register_metadata",
+            Operation::RegisterDiscoveryConfig => "register_discovery_config",
+            Operation::Offline => "offline",
+            Operation::Heartbeat => "heartbeat",
+        }
+    }
+}
+
+/// The requests sent for `operation` in `namespace`, about `route` for
+/// metadata. Logins have no namespace.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Call<'a> {
+    pub(crate) operation: Operation,
+    pub(crate) namespace: &'a str,
+    pub(crate) route: Option<&'a str>,
+}
+
+impl<'a> Call<'a> {
+    fn new(operation: Operation, namespace: &'a str) -> Self {
+        Call {
+            operation,
+            namespace,
+            route: None,
+        }
+    }
+
+    /// The span of these requests, parent of their admin call spans.
+    fn span(&self) -> Span {
+        info_span!(
+            "shenyu.namespace",
+            namespace = self.namespace,
+            event_type = self.operation.as_str(),
+            route = self.route
+        )
+    }
+}
+
+/// The span of a call to the admin server `server`, its latency and
+/// outcome recorded by [`ShenyuClient::finish_call`].
+fn admin_span(server: &str, attempt: usize) -> Span {
+    info_span!(
+        "shenyu.admin",
+        server,
+        attempt,
+        latency_ms = field::Empty,
+        outcome = field::Empty
+    )
+}
+
+/// How a call to shenyu admin ended, as recorded in metrics and the
+/// journal.
+fn outcome(error: Option<&ShenYuError>) -> &'static str {
+    match error {
+        None => "success",
+        Some(ShenYuError::AdminRejection { .. }) => "rejected",
+        Some(_) => "error",
+    }
+}
+
+/// The admin server an endpoint url belongs to.
+fn admin_server(url: &str) -> &str {
+    [
+        REGISTER_META_DATA_SUFFIX,
+        REGISTER_URI_SUFFIX,
+        PLATFORM_LOGIN_SUFFIX,
+        REGISTER_DISCOVERY_CONFIG_SUFFIX,
+        REGISTER_OFFLINE_SUFFIX,
+    ]
+    .iter()
+    .find_map(|suffix| url.strip_suffix(suffix))
+    .unwrap_or(url)
+}
+
+/// A payload registered in a namespace.
+trait Namespaced: Clone {
+    /// The payload for admins predating namespaces.
+    fn without_namespace(&self) -> Self;
+}
+
+impl Namespaced for UriRegistration {
+    fn without_namespace(&self) -> Self {
+        UriRegistration {
+            namespace_id: None,
+            ..self.clone()
+        }
+    }
+}
+
+impl Namespaced for MetadataRegistration {
+    fn without_namespace(&self) -> Self {
+        MetadataRegistration {
+            namespace_id: None,
+            ..self.clone()
+        }
+    }
+}
+
+impl Namespaced for DiscoveryConfigRegistration {
+    fn without_namespace(&self) -> Self {
+        DiscoveryConfigRegistration {
+            namespace_id: None,
+            ..self.clone()
+        }
+    }
+}
+
+/// A route's metadata, as registered with shenyu admin.
+struct Metadata<'a> {
+    path: &'a str,
+    method: Option<&'a str>,
+    rule_name: &'a str,
+    enabled: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{HostMode, ProxyConfig, RegisterProps, ShenYuConfigBuilder};
+    use crate::secret::Secret;
+    use crate::status::RegistrationState;
+
+    fn builder(servers: &str) -> ShenYuConfigBuilder {
+        ShenYuConfig::builder()
+            .servers(servers)
+            .register_prop("username", "admin")
+            .register_prop("password", "123456")
+            .host("10.0.0.1")
+            .host_mode(HostMode::Literal)
+            .context_path("/orders")
+    }
+
+    fn state(servers: &str, namespace_id: &str, context_path: &str) -> ClientState {
+        let config = builder(servers)
+            .namespace_id(namespace_id)
+            .context_path(context_path)
+            .build();
+        ClientState::new(config).unwrap()
+    }
+
+    fn state_config(servers: &str) -> ShenYuConfig {
+        builder(servers).build()
+    }
+
+    /// A shenyu admin on a local port, answering each request with
+    /// `answer(method, path)` and keeping the `"<method> <path>"` of every
+    /// request it got.
+    struct StubAdmin {
+        url: String,
+        requests: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl StubAdmin {
+        fn start<F>(answer: F) -> Self
+        where
+            F: Fn(&str, &str) -> (u16, String) + Send + 'static,
+        {
+            use std::io::{BufRead, BufReader, Read, Write};
+
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let seen = requests.clone();
+            _ = std::thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let mut reader = BufReader::new(stream);
+                    let mut line = String::new();
+                    _ = reader.read_line(&mut line);
+                    let mut parts = line.split_whitespace();
+                    let method = parts.next().unwrap_or_default().to_string();
+                    let path = parts.next().unwrap_or_default();
+                    let path = path.split('?').next().unwrap_or_default().to_string();
+                    let mut length = 0;
+                    loop {
+                        let mut header = String::new();
+                        if reader.read_line(&mut header).unwrap_or(0) == 0
+                            || header.trim().is_empty()
+                        {
+                            break;
+                        }
+                        if let Some((name, value)) = header.split_once(':') {
+                            if name.eq_ignore_ascii_case("content-length") {
+                                length = value.trim().parse().unwrap_or(0);
+                            }
+                        }
+                    }
+                    let mut body = vec![0; length];
+                    _ = reader.read_exact(&mut body);
+                    seen.lock().unwrap().push(format!("{method} {path}"));
+                    let (status, body) = answer(&method, &path);
+                    _ = write!(
+                        reader.into_inner(),
+                        "HTTP/1.1 {status} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                }
+            });
+            StubAdmin { url, requests }
+        }
+
+        /// An admin accepting the login and every registration.
+        fn accepting() -> Self {
+            StubAdmin::start(|_, path| match path {
+                PLATFORM_LOGIN_SUFFIX => {
+                    (200, r#"{"code":200,"data":{"token":"t0ken"}}"#.to_string())
+                }
+                _ => (200, "success".to_string()),
+            })
+        }
+
+        fn requests(&self) -> Vec<String> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    fn uri_infos() -> [UriInfo; 1] {
+        [UriInfo {
+            path: "/health".to_string(),
+            rule_name: "/health".to_string(),
+            service_name: None,
+            method_name: "health".to_string(),
+        }]
+    }
+
+    #[test]
+    fn test_diff_unchanged() {
+        let old = state("http://a:9095", "dev", "/orders");
+        let new = state("http://a:9095", "dev", "/orders");
+        assert_eq!(
+            RegistrationDiff::between(&old, &new),
+            RegistrationDiff::default()
+        );
+    }
+
+    #[test]
+    fn test_diff_removed_servers_and_namespaces() {
+        let old = state("http://a:9095,http://b:9095", "dev;test", "/orders");
+        let new = state("http://b:9095,http://c:9095", "dev", "/orders");
+        let diff = RegistrationDiff::between(&old, &new);
+        assert_eq!(diff.removed_servers, ["http://a:9095"]);
+        assert_eq!(diff.removed_namespaces, ["test"]);
+        assert!(!diff.uri_changed);
+        assert!(diff.changed);
+    }
+
+    #[test]
+    fn test_targets() {
+        let target = |servers: &str, namespace_id: &str| RegisterConfig {
+            servers: servers.to_string(),
+            namespace_id: Some(namespace_id.to_string()),
+            props: RegisterProps {
+                username: "admin".to_string(),
+                password: Secret::new("123456"),
+                ..RegisterProps::default()
+            },
+            ..RegisterConfig::default()
+        };
+        let config = |targets: &[(&str, RegisterConfig)]| {
+            let builder = targets.iter().fold(
+                ShenYuConfig::builder()
+                    .host("10.0.0.1")
+                    .host_mode(HostMode::Literal)
+                    .context_path("/orders"),
+                |builder, (name, register)| builder.target(name, register.clone()),
             );
+            ClientState::new(builder.build()).unwrap()
+        };
+
+        let old = config(&[
+            ("internal", target("http://a:9095", "dev")),
+            ("partner", target("https://p:9095", "orders")),
+        ]);
+        assert_eq!(old.targets.len(), 2);
+        assert_eq!(old.target("partner").unwrap().namespace_ids, ["orders"]);
+
+        let new = config(&[("internal", target("http://a:9095,http://b:9095", "dev"))]);
+        let diff = RegistrationDiff::between(&old, &new);
+        assert_eq!(diff.removed_targets, ["partner"]);
+        assert!(diff.removed_servers.is_empty());
+        assert!(diff.changed);
+    }
+
+    #[test]
+    fn test_refresh_servers() {
+        let path = std::env::temp_dir().join(format!("shenyu-refresh-{}", std::process::id()));
+        std::fs::write(&path, "http://127.0.0.1:1\n").unwrap();
+        let config = state_config(&format!("file://{}", path.display()));
+        let client = ShenyuClient::new(config, "app", &[], 3000).unwrap();
+        assert!(client.refresh_servers().targets.is_empty());
+
+        std::fs::write(&path, "http://127.0.0.1:1,http://127.0.0.1:2\n").unwrap();
+        let report = client.refresh_servers();
+        _ = std::fs::remove_file(&path);
+        assert_eq!(report.targets.len(), 1);
+        let state = client.state();
+        assert_eq!(
+            state.targets[0].register_uri_list,
+            [
+                format!("http://127.0.0.1:1{REGISTER_URI_SUFFIX}"),
+                format!("http://127.0.0.1:2{REGISTER_URI_SUFFIX}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_refresh_servers_moves_registration() {
+        let (old, new) = (StubAdmin::accepting(), StubAdmin::accepting());
+        let path = std::env::temp_dir().join(format!("shenyu-moves-{}", std::process::id()));
+        std::fs::write(&path, &old.url).unwrap();
+        let config = state_config(&format!("file://{}", path.display()));
+        let client = ShenyuClient::new(config, "app", &uri_infos(), 3000).unwrap();
+        assert!(client.register().unwrap().is_success());
+        assert!(client.admin_versions.contains_key(&old.url));
+
+        std::fs::write(&path, &new.url).unwrap();
+        assert!(client.refresh_servers().is_success());
+        assert_eq!(
+            old.requests().last(),
+            Some(&format!("POST {REGISTER_OFFLINE_SUFFIX}"))
+        );
+        assert!(new
+            .requests()
+            .contains(&format!("POST {REGISTER_URI_SUFFIX}")));
+        assert!(!client.admin_versions.contains_key(&old.url));
+
+        _ = client.offline_register();
+        let sent = old.requests().len();
+        std::fs::write(&path, &old.url).unwrap();
+        let report = client.refresh_servers();
+        _ = std::fs::remove_file(&path);
+        assert!(report.targets.is_empty());
+        assert_eq!(old.requests().len(), sent);
+    }
+
+    #[derive(Default)]
+    struct Recorder(std::sync::Mutex<Vec<String>>);
+
+    impl RegistrationListener for Recorder {
+        fn on_token_acquired(&self, event: &AdminEvent<'_, Login>) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("token acquired {}", event.server));
+        }
+
+        fn on_token_failed(&self, event: &AdminEvent<'_, Login>, error: &ShenYuError) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("token failed {}: {error}", event.server));
+        }
+
+        fn on_route_registered(&self, event: &AdminEvent<'_, MetadataRegistration>) {
+            self.0.lock().unwrap().push(format!(
+                "route registered {} {}",
+                event.server, event.payload.path
+            ));
+        }
+
+        fn on_route_failed(&self, event: &AdminEvent<'_, MetadataRegistration>, _: &ShenYuError) {
+            self.0.lock().unwrap().push(format!(
+                "route failed {} {}",
+                event.server, event.payload.path
+            ));
+        }
+
+        fn on_heartbeat_lost(&self, event: &AdminEvent<'_, UriRegistration>, _: &ShenYuError) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("heartbeat lost {}", event.server));
+        }
+
+        fn on_offline_completed(
+            &self,
+            event: &AdminEvent<'_, UriRegistration>,
+            result: Result<(), &ShenYuError>,
+        ) {
+            self.0.lock().unwrap().push(format!(
+                "offline {} {}",
+                event.payload.event_type,
+                result.is_ok()
+            ));
+        }
+    }
+
+    #[test]
+    fn test_listener() {
+        let recorder = Arc::new(Recorder::default());
+        let client = ShenyuClient::new(
+            state_config("http://127.0.0.1:1"),
+            "app",
+            &uri_infos(),
+            3000,
+        )
+        .unwrap()
+        .listener(recorder.clone());
+        _ = client.register_all_metadata(true);
+        _ = client.heartbeat();
+        _ = client.heartbeat();
+        _ = client.offline_register();
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            [
+                "route failed http://127.0.0.1:1 /orders/health",
+                "heartbeat lost http://127.0.0.1:1",
+                "offline OFFLINE false",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_listener_login() {
+        let recorder = Arc::new(Recorder::default());
+        let config = builder("http://127.0.0.1:1")
+            .register_prop("password", "pa55-in-query")
+            .build();
+        let client = ShenyuClient::new(config, "app", &[], 3000)
+            .unwrap()
+            .listener(recorder.clone());
+        _ = client.register();
+        let events = recorder.0.lock().unwrap().clone();
+        assert!(events[0].starts_with("token failed http://127.0.0.1:1: "));
+        assert!(!events[0].contains("pa55-in-query"));
+
+        let admin = StubAdmin::accepting();
+        let recorder = Arc::new(Recorder::default());
+        let client = ShenyuClient::new(state_config(&admin.url), "app", &uri_infos(), 3000)
+            .unwrap()
+            .listener(recorder.clone());
+        assert!(client.register().unwrap().is_success());
+        assert_eq!(
+            *recorder.0.lock().unwrap(),
+            [
+                format!("token acquired {}", admin.url),
+                format!("route registered {} /orders/health", admin.url),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_listener_reloads() {
+        #[derive(Default)]
+        struct Reloader {
+            client: std::sync::OnceLock<std::sync::Weak<ShenyuClient>>,
+            reloads: std::sync::atomic::AtomicUsize,
+        }
+
+        impl RegistrationListener for Reloader {
+            fn on_token_failed(&self, _: &AdminEvent<'_, Login>, _: &ShenYuError) {
+                let client = self.client.get().and_then(std::sync::Weak::upgrade);
+                if let Some(client) = client {
+                    assert!(client.reload(client.config()).is_ok());
+                    _ = self.reloads.fetch_add(1, Ordering::Relaxed);
+                }
+            }
         }
+
+        let reloader = Arc::new(Reloader::default());
+        let config = builder("http://127.0.0.1:1").build();
+        let client = Arc::new(
+            ShenyuClient::new(config, "app", &[], 3000)
+                .unwrap()
+                .listener(reloader.clone()),
+        );
+        _ = reloader.client.set(Arc::downgrade(&client));
+        assert!(client.register().is_err());
+        assert_eq!(reloader.reloads.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_status() {
+        let client = ShenyuClient::new(
+            state_config("http://127.0.0.1:1"),
+            "app",
+            &uri_infos(),
+            3000,
+        )
+        .unwrap();
+        let status = client.status();
+        assert_eq!(status.namespaces.len(), 1);
+        assert_eq!(status.namespaces[0].state, RegistrationState::Unregistered);
+
+        assert!(client.register().is_err());
+        let status = client.status();
+        let namespace = status
+            .namespace("default", SYS_DEFAULT_NAMESPACE_ID)
+            .unwrap();
+        assert_eq!(namespace.state, RegistrationState::Unregistered);
+        assert!(namespace.last_error.is_some());
+        assert!(namespace.last_success.is_none());
+        assert!(!namespace.routes["/health"].registered);
+        assert!(!status.is_registered());
+
+        let admin = StubAdmin::accepting();
+        let client =
+            ShenyuClient::new(state_config(&admin.url), "app", &uri_infos(), 3000).unwrap();
+        assert!(client.register().unwrap().is_success());
+        let status = client.status();
+        let namespace = status
+            .namespace("default", SYS_DEFAULT_NAMESPACE_ID)
+            .unwrap();
+        assert_eq!(namespace.state, RegistrationState::Registered);
+        assert!(namespace.routes["/health"].registered);
+        assert!(status.is_registered());
+
+        assert!(client.offline_register().is_success());
+        _ = client.heartbeat();
+        let status = client.status();
+        assert_eq!(status.namespaces[0].state, RegistrationState::Offline);
+    }
+
+    #[test]
+    fn test_no_reload_after_offline() {
+        let admin = StubAdmin::accepting();
+        let client =
+            ShenyuClient::new(state_config(&admin.url), "app", &uri_infos(), 3000).unwrap();
+        assert!(client.register().unwrap().is_success());
+        assert!(client.offline_register().is_success());
+        let sent = admin.requests().len();
+
+        let config = builder(&admin.url).context_path("/payments").build();
+        assert!(client.reload(config).unwrap().changed);
+        assert_eq!(admin.requests().len(), sent);
+        assert_eq!(client.state().env.uri.context_path, "/payments");
+        assert_eq!(
+            client.status().namespaces[0].state,
+            RegistrationState::Offline
+        );
+    }
+
+    #[test]
+    fn test_no_heartbeat_after_offline() {
+        let admin = StubAdmin::accepting();
+        let client =
+            ShenyuClient::new(state_config(&admin.url), "app", &uri_infos(), 3000).unwrap();
+        assert!(client.register().unwrap().is_success());
+        assert!(client.offline_register().is_success());
+        let sent = admin.requests().len();
+        assert!(client.heartbeat().targets.is_empty());
+        assert_eq!(admin.requests().len(), sent);
+
+        assert!(client.register().unwrap().is_success());
+        assert!(client.heartbeat().is_success());
+        assert_eq!(
+            admin.requests().last().map(String::as_str),
+            Some(format!("POST {REGISTER_URI_SUFFIX}").as_str())
+        );
+    }
+
+    #[test]
+    fn test_journal() {
+        let path = std::env::temp_dir().join(format!("shenyu-audit-{}", std::process::id()));
+        let journal = Arc::new(AuditJournal::open(&path).unwrap());
+        let client = ShenyuClient::new(state_config("http://127.0.0.1:1"), "app", &[], 3000)
+            .unwrap()
+            .journal(journal.clone());
+        _ = client.offline_register();
+        let records = journal.read().unwrap();
+        _ = std::fs::remove_file(&path);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].server, "http://127.0.0.1:1");
+        assert_eq!(records[0].namespace_id, SYS_DEFAULT_NAMESPACE_ID);
+        assert_eq!(records[0].event_type, EventType::OFFLINE);
+        assert_eq!(records[0].result, "error");
+        assert!(records[0].error.is_some());
+
+        let admin = StubAdmin::accepting();
+        let journal = Arc::new(AuditJournal::open(&path).unwrap());
+        let client = ShenyuClient::new(state_config(&admin.url), "app", &[], 3000)
+            .unwrap()
+            .journal(journal.clone());
+        assert!(client.heartbeat().is_success());
+        let records = journal.read().unwrap();
+        _ = std::fs::remove_file(&path);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].server, admin.url);
+        assert_eq!(records[0].event_type, EventType::UPDATED);
+        assert_eq!(records[0].operation, "heartbeat");
+        assert_eq!(records[0].result, "success");
+        assert!(records[0].error.is_none());
+    }
+
+    #[test]
+    fn test_diagnose() {
+        let client =
+            ShenyuClient::new(state_config("http://127.0.0.1:1"), "app", &[], 3000).unwrap();
+        let diagnosis = client.diagnose();
+        let checks: Vec<(CheckKind, CheckStatus)> = diagnosis
+            .checks
+            .iter()
+            .map(|check| (check.kind, check.status))
+            .collect();
+        assert_eq!(
+            checks,
+            [
+                (CheckKind::UrlParses, CheckStatus::Passed),
+                (CheckKind::DnsResolves, CheckStatus::Passed),
+                (CheckKind::Connects, CheckStatus::Failed),
+                (CheckKind::Authenticates, CheckStatus::Skipped),
+                (CheckKind::NamespaceExists, CheckStatus::Skipped),
+                (CheckKind::HostNotLoopback, CheckStatus::Passed),
+            ]
+        );
+        assert!(!diagnosis.is_healthy());
+        assert!(diagnosis.checks[2].hint.is_some());
+        assert!(diagnosis.to_string().contains("[FAILED] connects default"));
+    }
+
+    #[test]
+    fn test_diagnose_through_proxy() {
+        let proxy = StubAdmin::start(|_, path| {
+            if path.ends_with(PLATFORM_LOGIN_SUFFIX) {
+                (
+                    200,
+                    r#"{"code":500,"message":"bad credentials"}"#.to_string(),
+                )
+            } else {
+                (200, "success".to_string())
+            }
+        });
+        let config = builder("http://shenyu-admin.invalid:9095")
+            .register_prop("password", "pa55-in-query")
+            .proxy(ProxyConfig {
+                url: Some(proxy.url.clone()),
+                no_proxy: Some("localhost".to_string()),
+                ..ProxyConfig::default()
+            })
+            .build();
+        let client = ShenyuClient::new(config, "app", &[], 3000).unwrap();
+        let diagnosis = client.diagnose();
+        let checks: Vec<(CheckKind, CheckStatus)> = diagnosis
+            .checks
+            .iter()
+            .map(|check| (check.kind, check.status))
+            .collect();
+        assert_eq!(
+            checks,
+            [
+                (CheckKind::UrlParses, CheckStatus::Passed),
+                (CheckKind::DnsResolves, CheckStatus::Skipped),
+                (CheckKind::Connects, CheckStatus::Passed),
+                (CheckKind::Authenticates, CheckStatus::Failed),
+                (CheckKind::NamespaceExists, CheckStatus::Skipped),
+                (CheckKind::HostNotLoopback, CheckStatus::Passed),
+            ]
+        );
+        assert!(!diagnosis.to_string().contains("pa55-in-query"));
+        assert!(proxy
+            .requests()
+            .iter()
+            .all(|request| request.starts_with("GET http://shenyu-admin.invalid:9095/")));
+    }
+
+    #[test]
+    fn test_login_error_hides_password() {
+        let config = builder("http://127.0.0.1:1")
+            .register_prop("password", "pa55-in-query")
+            .build();
+        let client = ShenyuClient::new(config, "app", &[], 3000).unwrap();
+        let state = client.state();
+        let e = client.get_register_token(&state.targets[0]).unwrap_err();
+        assert!(!e.to_string().contains("pa55-in-query"));
+        assert!(!format!("{e:?}").contains("pa55-in-query"));
+        assert!(e.to_string().contains("http://127.0.0.1:1"));
+
+        let e = client.register().unwrap_err();
+        assert!(e.to_string().contains("login: "));
+        assert!(!e.to_string().contains("pa55-in-query"));
+    }
+
+    #[test]
+    fn test_admin_without_namespaces() {
+        let path = std::env::temp_dir().join(format!("shenyu-legacy-{}", std::process::id()));
+        let journal = Arc::new(AuditJournal::open(&path).unwrap());
+        let config = builder("http://127.0.0.1:1")
+            .namespace_id("dev;test")
+            .admin_version("2.5.0")
+            .build();
+        let client = ShenyuClient::new(config, "app", &[], 3000)
+            .unwrap()
+            .journal(journal.clone());
+        _ = client.offline_register();
+        _ = client.register_discovery_config();
+        let records = journal.read().unwrap();
+        _ = std::fs::remove_file(&path);
+        let namespaces: Vec<&str> = records
+            .iter()
+            .map(|record| record.namespace_id.as_str())
+            .collect();
+        assert_eq!(namespaces, ["dev"]);
+
+        let state = client.state();
+        let payload = client.uri_registration(&state, "dev", EventType::OFFLINE);
+        let json = serde_json::to_value(payload.without_namespace()).unwrap();
+        assert!(json.get("namespaceId").is_none());
+        assert_eq!(json["eventType"], "OFFLINE");
+    }
+
+    #[test]
+    fn test_detect_admin_version() {
+        let legacy = StubAdmin::start(|method, path| match (method, path) {
+            ("GET", NAMESPACE_LIST_SUFFIX | REGISTER_DISCOVERY_CONFIG_SUFFIX) => {
+                (404, String::new())
+            }
+            _ => (200, r#"{"code":200,"data":{"token":"t0ken"}}"#.to_string()),
+        });
+        let client = ShenyuClient::new(state_config(&legacy.url), "app", &[], 3000).unwrap();
+        _ = client.register();
+        assert_eq!(
+            client
+                .admin_versions
+                .get(&legacy.url)
+                .map(|version| *version),
+            Some(AdminVersion::LEGACY)
+        );
+
+        let unavailable = StubAdmin::start(|method, path| match (method, path) {
+            ("GET", NAMESPACE_LIST_SUFFIX) => (503, String::new()),
+            _ => (200, r#"{"code":200,"data":{"token":"t0ken"}}"#.to_string()),
+        });
+        let client = ShenyuClient::new(state_config(&unavailable.url), "app", &[], 3000).unwrap();
+        _ = client.register();
+        assert!(client.admin_versions.is_empty());
+    }
+
+    #[test]
+    fn test_diff_uri_changed() {
+        let old = state("http://a:9095", "dev", "/orders");
+        let new = state("http://a:9095", "dev", "/orders-v2");
+        let diff = RegistrationDiff::between(&old, &new);
+        assert!(diff.uri_changed);
+        assert!(diff.changed);
     }
 }
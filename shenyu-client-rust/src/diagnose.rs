@@ -0,0 +1,332 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Connectivity checks against the admin servers, see
+//! [`crate::core::ShenyuClient::diagnose`].
+
+use crate::transport::{self, Transport};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use ureq::OrAnyStatus;
+use url::Url;
+
+/// What a [`Check`] verifies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckKind {
+    /// The admin url parses.
+    UrlParses,
+    /// The admin host resolves.
+    DnsResolves,
+    /// A TCP connection, and the TLS handshake for https, succeeds.
+    Connects,
+    /// Logging in at `PLATFORM_LOGIN_SUFFIX` returns a token.
+    Authenticates,
+    /// The namespace exists in the admin.
+    NamespaceExists,
+    /// The host advertised to the gateway is not a loopback address.
+    HostNotLoopback,
+}
+
+impl Display for CheckKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CheckKind::UrlParses => "url parses",
+            CheckKind::DnsResolves => "dns resolves",
+            CheckKind::Connects => "connects",
+            CheckKind::Authenticates => "authenticates",
+            CheckKind::NamespaceExists => "namespace exists",
+            CheckKind::HostNotLoopback => "host is not loopback",
+        })
+    }
+}
+
+/// How a [`Check`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    /// The check succeeded.
+    Passed,
+    /// The check failed, see its hint.
+    Failed,
+    /// The check wasn't run, as an earlier one failed or it doesn't apply.
+    Skipped,
+}
+
+/// A check and its result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Check {
+    /// What was checked.
+    pub kind: CheckKind,
+    /// The target checked, if any.
+    pub target: Option<String>,
+    /// The admin server checked, if any.
+    pub server: Option<String>,
+    /// The namespace checked, if any.
+    pub namespace_id: Option<String>,
+    /// How the check ended.
+    pub status: CheckStatus,
+    /// What was found.
+    pub detail: String,
+    /// How to fix a failure.
+    pub hint: Option<String>,
+}
+
+impl Check {
+    pub(crate) fn new(kind: CheckKind, target: &str, server: &str) -> Self {
+        Check {
+            kind,
+            target: Some(target.to_string()),
+            server: Some(server.to_string()),
+            namespace_id: None,
+            status: CheckStatus::Passed,
+            detail: String::new(),
+            hint: None,
+        }
+    }
+
+    pub(crate) fn passed(mut self, detail: String) -> Self {
+        self.status = CheckStatus::Passed;
+        self.detail = detail;
+        self
+    }
+
+    pub(crate) fn failed(mut self, detail: String, hint: String) -> Self {
+        self.status = CheckStatus::Failed;
+        self.detail = detail;
+        self.hint = Some(hint);
+        self
+    }
+
+    pub(crate) fn skipped(mut self, detail: &str) -> Self {
+        self.status = CheckStatus::Skipped;
+        self.detail = detail.to_string();
+        self
+    }
+}
+
+/// The checks run by [`crate::core::ShenyuClient::diagnose`], in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Diagnosis {
+    /// Every check, per target and admin server, then the host check.
+    pub checks: Vec<Check>,
+}
+
+impl Diagnosis {
+    /// Whether no check failed.
+    #[must_use]
+    pub fn is_healthy(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// The checks that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &Check> {
+        self.checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Failed)
+    }
+}
+
+impl Display for Diagnosis {
+    /// One line per check, with the hint of failures below.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for check in &self.checks {
+            let status = match check.status {
+                CheckStatus::Passed => "ok",
+                CheckStatus::Failed => "FAILED",
+                CheckStatus::Skipped => "skipped",
+            };
+            write!(f, "[{status}] {}", check.kind)?;
+            for scope in [&check.target, &check.server, &check.namespace_id]
+                .into_iter()
+                .flatten()
+            {
+                write!(f, " {scope}")?;
+            }
+            writeln!(f, ": {}", check.detail)?;
+            if let Some(hint) = &check.hint {
+                writeln!(f, "    hint: {hint}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check `server` parses, resolves and accepts connections, returning the
+/// checks run and whether they all passed.
+pub(crate) fn reach(
+    target: &str,
+    server: &str,
+    transport: &Transport,
+    connect_timeout: Duration,
+) -> (Vec<Check>, bool) {
+    let mut checks = vec![];
+    let url = match Url::parse(server) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") && url.host_str().is_some() => {
+            checks.push(Check::new(CheckKind::UrlParses, target, server).passed(url.to_string()));
+            url
+        }
+        result => {
+            let detail = result.map_or_else(
+                |e| e.to_string(),
+                |url| format!("{url} has no http(s) host"),
+            );
+            checks.push(
+                Check::new(CheckKind::UrlParses, target, server).failed(
+                    detail,
+                    "Fix register.servers: each admin url must look like http://shenyu-admin:9095"
+                        .to_string(),
+                ),
+            );
+            return (checks, false);
+        }
+    };
+
+    let host = url.host_str().unwrap_or_default();
+    let port = url.port_or_known_default().unwrap_or(80);
+    if transport.is_proxied(server) {
+        return reach_through_proxy(checks, target, server, transport, host);
+    }
+    let addrs: Vec<SocketAddr> = match (host, port).to_socket_addrs() {
+        Ok(addrs) => addrs.collect(),
+        Err(e) => {
+            checks.push(Check::new(CheckKind::DnsResolves, target, server).failed(
+                format!("{host}: {e}"),
+                format!(
+                    "Check the admin host name {host} is spelled right and resolvable from \
+                     this machine, e.g. with `nslookup {host}`"
+                ),
+            ));
+            return (checks, false);
+        }
+    };
+    let Some(addr) = addrs.first() else {
+        checks.push(Check::new(CheckKind::DnsResolves, target, server).failed(
+            format!("{host} has no address"),
+            format!("Add an A or AAAA record for {host}"),
+        ));
+        return (checks, false);
+    };
+    checks.push(Check::new(CheckKind::DnsResolves, target, server).passed(format!("{addrs:?}")));
+
+    let connects = Check::new(CheckKind::Connects, target, server);
+    if let Err(e) = TcpStream::connect_timeout(addr, connect_timeout) {
+        checks.push(connects.failed(
+            format!("tcp {addr}: {e}"),
+            format!(
+                "Check shenyu admin is running and listening on port {port}, and that no \
+                 firewall or proxy blocks {addr}"
+            ),
+        ));
+        return (checks, false);
+    }
+    if url.scheme() == "https" {
+        let result = transport.agent(server).get(server).call().or_any_status();
+        if let Err(e) = result {
+            let error = transport::error(server, e);
+            checks.push(
+                connects.failed(
+                    error.to_string(),
+                    "Check register.tls: ca_file must hold the admin's CA, and cert_file and \
+                 key_file the client certificate when the admin requires one"
+                        .to_string(),
+                ),
+            );
+            return (checks, false);
+        }
+        checks.push(connects.passed(format!("tcp {addr} and tls handshake succeeded")));
+    } else {
+        checks.push(connects.passed(format!("tcp {addr} connected")));
+    }
+    (checks, true)
+}
+
+/// Check `server` answers through the proxy, which resolves and connects
+/// to its `host` itself.
+fn reach_through_proxy(
+    mut checks: Vec<Check>,
+    target: &str,
+    server: &str,
+    transport: &Transport,
+    host: &str,
+) -> (Vec<Check>, bool) {
+    checks.push(
+        Check::new(CheckKind::DnsResolves, target, server).skipped("resolved by register.proxy"),
+    );
+    let connects = Check::new(CheckKind::Connects, target, server);
+    if let Err(e) = transport.agent(server).get(server).call().or_any_status() {
+        checks.push(connects.failed(
+            transport::error(server, e).to_string(),
+            format!(
+                "Check register.proxy.url is reachable and lets requests to {host} through, \
+                 or add {host} to register.proxy.no_proxy"
+            ),
+        ));
+        return (checks, false);
+    }
+    checks.push(connects.passed("answered through register.proxy".to_string()));
+    (checks, true)
+}
+
+/// Check the host advertised to the gateway isn't a loopback address, which
+/// the gateway would take for itself.
+pub(crate) fn host(host: &str) -> Check {
+    let check = Check {
+        kind: CheckKind::HostNotLoopback,
+        target: None,
+        server: None,
+        namespace_id: None,
+        status: CheckStatus::Passed,
+        detail: String::new(),
+        hint: None,
+    };
+    let loopback = match host.parse::<IpAddr>() {
+        Ok(ip) => ip.is_loopback(),
+        Err(_) => {
+            host.eq_ignore_ascii_case("localhost")
+                || (host, 0).to_socket_addrs().is_ok_and(|mut addrs| {
+                    let mut addrs = addrs.by_ref().peekable();
+                    addrs.peek().is_some() && addrs.all(|addr| addr.ip().is_loopback())
+                })
+        }
+    };
+    if loopback {
+        check.failed(
+            format!("{host} is a loopback address"),
+            "Set uri.host_mode to ip, or uri.host with host_mode literal, to an address \
+             the gateway can reach"
+                .to_string(),
+        )
+    } else {
+        check.passed(host.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host() {
+        assert_eq!(host("127.0.0.1").status, CheckStatus::Failed);
+        assert_eq!(host("::1").status, CheckStatus::Failed);
+        assert_eq!(host("localhost").status, CheckStatus::Failed);
+        assert_eq!(host("10.0.0.1").status, CheckStatus::Passed);
+    }
+}
@@ -15,27 +15,236 @@
 // specific language governing permissions and limitations
 // under the License.
 
-use std::io::Error;
+use crate::config::{ConfigErrors, ConfigIssue};
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// The underlying cause of a [`ShenYuError`].
+pub type BoxError = Box<dyn Error + Send + Sync>;
 
 /// Shenyu Error.
+///
+/// Each variant tells what the client was doing in `context`, and keeps the
+/// underlying error, if any, as its [`Error::source`].
 #[derive(Debug)]
-pub struct ShenYuError {
-    /// The error code.
-    pub code: i32,
-    /// The error message.
-    pub message: String,
+pub enum ShenYuError {
+    /// The configuration couldn't be loaded, or is invalid.
+    Config {
+        /// What was being loaded or checked.
+        context: String,
+        /// The underlying error.
+        source: Option<BoxError>,
+    },
+    /// The host advertised to shenyu admin couldn't be determined.
+    AddressDetection {
+        /// How the host was being determined.
+        context: String,
+        /// The underlying error.
+        source: Option<BoxError>,
+    },
+    /// No admin token could be obtained.
+    Authentication {
+        /// The target and admin server logged in to.
+        context: String,
+        /// The underlying error.
+        source: Option<BoxError>,
+    },
+    /// A request to shenyu admin couldn't be sent, or its answer read.
+    Transport {
+        /// The request that failed.
+        context: String,
+        /// The underlying error.
+        source: Option<BoxError>,
+    },
+    /// Shenyu admin answered, but rejected the request.
+    AdminRejection {
+        /// The url of the request.
+        url: String,
+        /// The HTTP status of the answer.
+        status: u16,
+        /// The body of the answer.
+        message: String,
+    },
+    /// A payload or config couldn't be serialized or parsed.
+    Serialization {
+        /// What was being serialized or parsed.
+        context: String,
+        /// The underlying error.
+        source: Option<BoxError>,
+    },
+    /// The audit journal couldn't be written or read.
+    Journal {
+        /// The journal file being written or read.
+        context: String,
+        /// The underlying error.
+        source: Option<BoxError>,
+    },
 }
 
 impl ShenYuError {
-    /// Create a new `ShenYuError`.
+    /// Create a [`ShenYuError::Config`].
+    pub fn config<C: Into<String>, E: Into<BoxError>>(context: C, source: E) -> Self {
+        ShenYuError::Config {
+            context: context.into(),
+            source: Some(source.into()),
+        }
+    }
+
+    /// Create a [`ShenYuError::AddressDetection`].
+    pub fn address_detection<C: Into<String>, E: Into<BoxError>>(context: C, source: E) -> Self {
+        ShenYuError::AddressDetection {
+            context: context.into(),
+            source: Some(source.into()),
+        }
+    }
+
+    /// Create a [`ShenYuError::Authentication`].
+    pub fn authentication<C: Into<String>, E: Into<BoxError>>(context: C, source: E) -> Self {
+        ShenYuError::Authentication {
+            context: context.into(),
+            source: Some(source.into()),
+        }
+    }
+
+    /// Create a [`ShenYuError::Transport`].
+    pub fn transport<C: Into<String>, E: Into<BoxError>>(context: C, source: E) -> Self {
+        ShenYuError::Transport {
+            context: context.into(),
+            source: Some(source.into()),
+        }
+    }
+
+    /// Create a [`ShenYuError::Serialization`].
+    pub fn serialization<C: Into<String>, E: Into<BoxError>>(context: C, source: E) -> Self {
+        ShenYuError::Serialization {
+            context: context.into(),
+            source: Some(source.into()),
+        }
+    }
+
+    /// Create a [`ShenYuError::Journal`].
+    pub fn journal<C: Into<String>, E: Into<BoxError>>(context: C, source: E) -> Self {
+        ShenYuError::Journal {
+            context: context.into(),
+            source: Some(source.into()),
+        }
+    }
+
+    /// The problems found by [`crate::config::ShenYuConfig::validate`], when
+    /// the configuration was rejected for them.
     #[must_use]
-    pub fn new(code: i32, message: String) -> Self {
-        Self { code, message }
+    pub fn config_issues(&self) -> &[ConfigIssue] {
+        match self {
+            ShenYuError::Config {
+                source: Some(source),
+                ..
+            } => source
+                .downcast_ref::<ConfigErrors>()
+                .map_or(&[], |errors| errors.issues.as_slice()),
+            _ => &[],
+        }
+    }
+
+    /// What went wrong, without the details that may carry credentials.
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            ShenYuError::Config { .. } => "config error",
+            ShenYuError::AddressDetection { .. } => "address detection failed",
+            ShenYuError::Authentication { .. } => "authentication failed",
+            ShenYuError::Transport { .. } => "transport error",
+            ShenYuError::AdminRejection { .. } => "rejected by shenyu admin",
+            ShenYuError::Serialization { .. } => "serialization error",
+            ShenYuError::Journal { .. } => "audit journal error",
+        }
     }
 }
 
-impl From<ShenYuError> for Error {
+impl Display for ShenYuError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShenYuError::Config { context, source }
+            | ShenYuError::AddressDetection { context, source }
+            | ShenYuError::Authentication { context, source }
+            | ShenYuError::Transport { context, source }
+            | ShenYuError::Serialization { context, source }
+            | ShenYuError::Journal { context, source } => {
+                write!(f, "{}, {context}", self.kind())?;
+                match source {
+                    Some(source) => write!(f, ": {source}"),
+                    None => Ok(()),
+                }
+            }
+            ShenYuError::AdminRejection {
+                url,
+                status,
+                message,
+            } => write!(f, "{}, {url} answered {status}: {message}", self.kind()),
+        }
+    }
+}
+
+impl Error for ShenYuError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ShenYuError::Config { source, .. }
+            | ShenYuError::AddressDetection { source, .. }
+            | ShenYuError::Authentication { source, .. }
+            | ShenYuError::Transport { source, .. }
+            | ShenYuError::Serialization { source, .. }
+            | ShenYuError::Journal { source, .. } => source
+                .as_deref()
+                .map(|source| source as &(dyn Error + 'static)),
+            ShenYuError::AdminRejection { .. } => None,
+        }
+    }
+}
+
+impl From<ConfigErrors> for ShenYuError {
+    fn from(errors: ConfigErrors) -> Self {
+        ShenYuError::config("the shenyu config is invalid", errors)
+    }
+}
+
+impl From<ShenYuError> for std::io::Error {
     fn from(val: ShenYuError) -> Self {
-        Error::new(std::io::ErrorKind::Other, val.message)
+        std::io::Error::new(std::io::ErrorKind::Other, val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ShenYuConfig;
+
+    #[test]
+    fn test_display_and_source() {
+        let error = ShenYuError::transport(
+            "register uri to http://admin:9095",
+            std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "refused"),
+        );
+        assert_eq!(
+            error.to_string(),
+            "transport error, register uri to http://admin:9095: refused"
+        );
+        assert!(error.source().is_some());
+
+        let error = ShenYuError::AdminRejection {
+            url: "http://admin:9095/shenyu-client/register-uri".to_string(),
+            status: 200,
+            message: "token is error".to_string(),
+        };
+        assert!(error.to_string().contains("token is error"));
+        assert!(error.source().is_none());
+    }
+
+    #[test]
+    fn test_config_issues() {
+        let mut config = ShenYuConfig::default();
+        config.register.servers = String::new();
+        let error = ShenYuError::from(config.validate().unwrap_err());
+        assert_eq!(error.config_issues()[0].field, "register.servers");
+        assert!(ShenYuError::config("missing", "file")
+            .config_issues()
+            .is_empty());
     }
 }
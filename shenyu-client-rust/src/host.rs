@@ -0,0 +1,166 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use crate::config::{HostMode, UriConfig};
+use crate::error::ShenYuError;
+use dns_lookup::AddrInfoHints;
+use std::net::IpAddr;
+
+/// `AI_CANONNAME`, identical on linux, macos and windows.
+const AI_CANONNAME: i32 = 0x0002;
+
+/// Resolve the host advertised to shenyu admin according to `uri.host_mode`.
+pub fn resolve_host(uri: &UriConfig) -> Result<String, ShenYuError> {
+    match uri.host_mode {
+        HostMode::Ip => local_ip().map(|ip| ip.to_string()).ok_or_else(|| {
+            ShenYuError::address_detection(
+                "host_mode ip",
+                "no network interface has a usable address",
+            )
+        }),
+        HostMode::Hostname => dns_lookup::get_hostname()
+            .map_err(|e| ShenYuError::address_detection("host_mode hostname", e)),
+        HostMode::Fqdn => local_fqdn()
+            .and_then(|fqdn| validate_fqdn(&fqdn).map(|()| fqdn))
+            .map_err(|e| ShenYuError::address_detection("host_mode fqdn", e)),
+        HostMode::Literal => {
+            if uri.host.trim().is_empty() {
+                Err(ShenYuError::address_detection(
+                    "host_mode literal",
+                    "shenyu.uri.host is empty",
+                ))
+            } else {
+                Ok(uri.host.trim().to_string())
+            }
+        }
+    }
+}
+
+/// Detect the local ip address from the network interfaces.
+pub fn local_ip() -> Option<IpAddr> {
+    #[allow(unused_assignments)]
+    let mut host = None;
+    #[cfg(not(target_os = "macos"))]
+    {
+        host = match local_ip_address::local_ip() {
+            Ok(IpAddr::V4(ipv4)) => Some(IpAddr::V4(ipv4)),
+            Ok(IpAddr::V6(ipv6)) => ipv6.to_ipv4().map(IpAddr::V4),
+            _ => None,
+        };
+    }
+    #[cfg(target_os = "macos")]
+    {
+        use local_ip_address::macos;
+        for (_, ipaddr) in macos::list_afinet_netifas().unwrap_or_default() {
+            if ipaddr.is_loopback() {
+                continue;
+            }
+            host = match ipaddr {
+                IpAddr::V4(ipv4) => Some(IpAddr::from(ipv4)),
+                IpAddr::V6(_) => continue,
+            };
+        }
+    }
+    host
+}
+
+/// Find the fully qualified domain name of this machine.
+///
+/// The hostname is used if it is already qualified, otherwise its canonical
+/// name is looked up, falling back to a reverse lookup of the local ip.
+fn local_fqdn() -> Result<String, String> {
+    let hostname = dns_lookup::get_hostname()
+        .map_err(|e| format!("Failed to determine local hostname, cause {e}"))?;
+    if hostname.contains('.') {
+        return Ok(hostname);
+    }
+    let hints = AddrInfoHints {
+        flags: AI_CANONNAME,
+        ..AddrInfoHints::default()
+    };
+    if let Ok(infos) = dns_lookup::getaddrinfo(Some(&hostname), None, Some(hints)) {
+        let canonical = infos
+            .filter_map(Result::ok)
+            .filter_map(|info| info.canonname)
+            .find(|name| name.contains('.'));
+        if let Some(canonical) = canonical {
+            return Ok(canonical);
+        }
+    }
+    local_ip()
+        .and_then(|ip| dns_lookup::lookup_addr(&ip).ok())
+        .filter(|name| name.contains('.'))
+        .ok_or_else(|| format!("Failed to resolve the fully qualified domain name of {hostname}"))
+}
+
+/// Check that `fqdn` resolves to at least one non-loopback local address,
+/// so the gateway can reach this instance through it.
+fn validate_fqdn(fqdn: &str) -> Result<(), String> {
+    let resolved = dns_lookup::lookup_host(fqdn)
+        .map_err(|e| format!("Failed to resolve fqdn {fqdn}, cause {e}"))?;
+    let local: Vec<IpAddr> = local_ip_address::list_afinet_netifas()
+        .map_err(|e| format!("Failed to list local network interfaces, cause {e}"))?
+        .into_iter()
+        .map(|(_, ip)| ip)
+        .collect();
+    if resolved
+        .iter()
+        .any(|ip| !ip.is_loopback() && local.contains(ip))
+    {
+        Ok(())
+    } else {
+        Err(format!(
+            "fqdn {fqdn} resolves to {resolved:?}, which is not a non-loopback local address"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri_config(host: &str, host_mode: HostMode) -> UriConfig {
+        UriConfig {
+            app_name: "app".to_string(),
+            host: host.to_string(),
+            host_mode,
+            port: 8080,
+            context_path: "/app".to_string(),
+            environment: "test".to_string(),
+            rpc_type: "http".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_literal_host() {
+        let uri = uri_config(" gateway.internal ", HostMode::Literal);
+        assert_eq!(resolve_host(&uri).unwrap(), "gateway.internal");
+        let uri = uri_config("", HostMode::Literal);
+        assert!(resolve_host(&uri).is_err());
+    }
+
+    #[test]
+    fn test_hostname_host() {
+        let uri = uri_config("", HostMode::Hostname);
+        assert!(!resolve_host(&uri).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_validate_fqdn_rejects_loopback() {
+        assert!(validate_fqdn("localhost").is_err());
+    }
+}
@@ -0,0 +1,297 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! An append-only journal of the calls to shenyu admin, showing when the
+//! instance joined or left the gateway.
+//!
+//! Each registration, heartbeat and offline attempt is written as a line of
+//! JSON. The file is rotated to `<path>.1`, `<path>.2`... when it would grow
+//! past [`AuditJournal::max_bytes`].
+//!
+//! # Examples
+//! ```rust,no_run
+//! use shenyu_client_rust::config::ShenYuConfig;
+//! use shenyu_client_rust::core::ShenyuClient;
+//! use shenyu_client_rust::journal::AuditJournal;
+//! use std::sync::Arc;
+//!
+//! let journal = Arc::new(AuditJournal::open("shenyu-audit.jsonl").unwrap());
+//! let config = ShenYuConfig::from_yaml_file("config.yml").unwrap();
+//! let client = ShenyuClient::new(config, "app", &[], 3000)
+//!     .unwrap()
+//!     .journal(journal.clone());
+//! client.register().unwrap();
+//! for record in journal.read().unwrap() {
+//!     println!("{} {} {}", record.timestamp, record.event_type, record.result);
+//! }
+//! ```
+
+use crate::error::ShenYuError;
+use crate::model::EventType;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The size past which the journal is rotated, by default.
+pub const DEFAULT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// The rotated files kept, by default.
+pub const DEFAULT_MAX_FILES: usize = 5;
+
+/// A call to shenyu admin, as journaled.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    /// When the call ended, in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The name of the target.
+    pub target: String,
+    /// The admin server called.
+    pub server: String,
+    /// The admin namespace.
+    pub namespace_id: String,
+    /// `REGISTER`, `UPDATED` for heartbeats, or `OFFLINE`.
+    pub event_type: EventType,
+    /// What was sent: `register_uri`, `register_metadata`,
+    /// `register_discovery_config`, `heartbeat` or `offline`.
+    pub operation: String,
+    /// The route, for metadata.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
+    /// `success`, `rejected` by the admin, or `error`.
+    pub result: String,
+    /// Why the call failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AuditRecord {
+    /// A record timestamped now.
+    #[must_use]
+    pub fn now(
+        target: &str,
+        server: &str,
+        namespace_id: &str,
+        event_type: EventType,
+        operation: &str,
+    ) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| {
+                u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX)
+            });
+        AuditRecord {
+            timestamp,
+            target: target.to_string(),
+            server: server.to_string(),
+            namespace_id: namespace_id.to_string(),
+            event_type,
+            operation: operation.to_string(),
+            route: None,
+            result: "success".to_string(),
+            error: None,
+        }
+    }
+}
+
+/// An append-only JSON-lines file of [`AuditRecord`]s, rotated by size.
+///
+/// Add it to a client with [`crate::core::ShenyuClient::journal`]. Failing
+/// to write a record is logged and doesn't fail the registration.
+#[derive(Debug)]
+pub struct AuditJournal {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    file: Mutex<JournalFile>,
+}
+
+#[derive(Debug)]
+struct JournalFile {
+    file: File,
+    len: u64,
+}
+
+impl AuditJournal {
+    /// Open the journal at `path`, appending to it if it exists.
+    pub fn open<P: Into<PathBuf>>(path: P) -> Result<Self, ShenYuError> {
+        let path = path.into();
+        let file = JournalFile::open(&path)?;
+        Ok(AuditJournal {
+            path,
+            max_bytes: DEFAULT_MAX_BYTES,
+            max_files: DEFAULT_MAX_FILES,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Rotate the journal before it grows past `max_bytes`.
+    #[must_use]
+    pub fn max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Keep `max_files` rotated files, deleting older ones.
+    #[must_use]
+    pub fn max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    /// The path of the current journal file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append `record`, rotating the file first if it would grow too big.
+    pub fn append(&self, record: &AuditRecord) -> Result<(), ShenYuError> {
+        let mut line = serde_json::to_vec(record)
+            .map_err(|e| ShenYuError::serialization("serialize an audit record", e))?;
+        line.push(b'\n');
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        let len = u64::try_from(line.len()).unwrap_or(u64::MAX);
+        if file.len > 0 && file.len.saturating_add(len) > self.max_bytes {
+            self.rotate()?;
+            *file = JournalFile::open(&self.path)?;
+        }
+        file.file
+            .write_all(&line)
+            .map_err(|e| ShenYuError::journal(format!("append to {}", self.path.display()), e))?;
+        file.len += len;
+        Ok(())
+    }
+
+    /// Every record still kept, oldest first, from the rotated files then
+    /// the current one. Lines that aren't records, such as one cut short
+    /// by a crash, are skipped.
+    pub fn read(&self) -> Result<Vec<AuditRecord>, ShenYuError> {
+        // Not appended to while reading.
+        let _file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut records = vec![];
+        for index in (0..=self.max_files).rev() {
+            let path = self.rotated(index);
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(ShenYuError::journal(format!("open {}", path.display()), e)),
+            };
+            for line in BufReader::new(file).lines() {
+                let line =
+                    line.map_err(|e| ShenYuError::journal(format!("read {}", path.display()), e))?;
+                if let Ok(record) = serde_json::from_str(&line) {
+                    records.push(record);
+                }
+            }
+        }
+        Ok(records)
+    }
+
+    /// Shift `<path>.N` to `<path>.N+1`, dropping the oldest, and the
+    /// current file to `<path>.1`.
+    fn rotate(&self) -> Result<(), ShenYuError> {
+        for index in (0..=self.max_files).rev() {
+            let from = self.rotated(index);
+            let result = if index == self.max_files {
+                std::fs::remove_file(&from)
+            } else {
+                std::fs::rename(&from, self.rotated(index + 1))
+            };
+            match result {
+                Err(e) if e.kind() != ErrorKind::NotFound => {
+                    return Err(ShenYuError::journal(
+                        format!("rotate {}", from.display()),
+                        e,
+                    ));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// The current file for 0, else the rotated file `index`.
+    fn rotated(&self, index: usize) -> PathBuf {
+        if index == 0 {
+            return self.path.clone();
+        }
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+        path.into()
+    }
+}
+
+impl JournalFile {
+    fn open(path: &Path) -> Result<Self, ShenYuError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| ShenYuError::journal(format!("open {}", path.display()), e))?;
+        let len = file
+            .metadata()
+            .map_err(|e| ShenYuError::journal(format!("stat {}", path.display()), e))?
+            .len();
+        Ok(JournalFile { file, len })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(namespace_id: &str) -> AuditRecord {
+        AuditRecord::now(
+            "default",
+            "http://127.0.0.1:9095",
+            namespace_id,
+            EventType::REGISTER,
+            "register_uri",
+        )
+    }
+
+    #[test]
+    fn test_append_rotate_read() {
+        let dir = std::env::temp_dir().join(format!("shenyu-journal-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.jsonl");
+        let line = serde_json::to_vec(&record("ns-0")).unwrap().len() as u64 + 1;
+        let journal = AuditJournal::open(&path)
+            .unwrap()
+            .max_bytes(2 * line)
+            .max_files(1);
+        for index in 0..5 {
+            journal.append(&record(&format!("ns-{index}"))).unwrap();
+        }
+        let namespaces: Vec<String> = journal
+            .read()
+            .unwrap()
+            .into_iter()
+            .map(|record| record.namespace_id)
+            .collect();
+        let rotated = dir.join("audit.jsonl.1").exists();
+        let dropped = dir.join("audit.jsonl.2").exists();
+        _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(namespaces, ["ns-2", "ns-3", "ns-4"]);
+        assert!(rotated);
+        assert!(!dropped);
+    }
+}
@@ -78,12 +78,38 @@ pub mod ci;
 pub mod config;
 /// Shenyu client core.
 pub mod core;
+/// Admin connectivity diagnostics.
+pub mod diagnose;
 /// Error handling.
 pub mod error;
+/// Local host detection.
+pub mod host;
+/// Audit journal of the admin calls.
+pub mod journal;
+/// Registration lifecycle listeners.
+pub mod listener;
 /// Macros.
 pub mod macros;
+/// Prometheus metrics.
+#[cfg(feature = "metrics")]
+pub mod metrics;
 /// Structs.
 pub mod model;
+/// OpenTelemetry context propagation.
+#[cfg(feature = "otel")]
+pub mod otel;
+/// Credentials kept out of logs.
+pub mod secret;
+/// Admin server resolution.
+pub mod servers;
+/// Registration state.
+pub mod status;
+/// Shenyu admin versions.
+pub mod version;
+/// Config hot reload, admin server refresh and heartbeats.
+pub mod watch;
+
+mod transport;
 
 #[allow(missing_docs)]
 pub trait IRouter {
@@ -227,6 +253,111 @@ pub mod axum_impl {
             &self.uri_infos
         }
 
+        /// Run the handlers of the routes added so far in a span carrying
+        /// the trace context forwarded by the gateway and the matched
+        /// `ShenYu` rule, see [`crate::otel`].
+        ///
+        /// Like `Router::route_layer`, it panics if no route was added.
+        #[cfg(feature = "otel")]
+        #[must_use]
+        pub fn trace_context(mut self) -> Self {
+            use axum::extract::MatchedPath;
+            use axum::middleware::{from_fn, Next};
+            use tracing::Instrument;
+
+            let uri_infos = std::sync::Arc::new(self.uri_infos.clone());
+            self.inner =
+                self.inner
+                    .route_layer(from_fn(move |request: Request, next: Next| {
+                        let uri_infos = uri_infos.clone();
+                        let parent = crate::otel::extract(request.headers().iter().filter_map(
+                            |(name, value)| Some((name.as_str(), value.to_str().ok()?)),
+                        ));
+                        let route = request
+                            .extensions()
+                            .get::<MatchedPath>()
+                            .map(|path| path.as_str().to_string());
+                        let span = crate::otel::request_span(
+                            request.method().as_str(),
+                            route.as_deref(),
+                            &uri_infos,
+                            parent,
+                        );
+                        next.run(request).instrument(span)
+                    }));
+            self
+        }
+
+        /// Serve `metrics` in the Prometheus text format at `path`.
+        ///
+        /// The route is not registered with shenyu admin.
+        #[cfg(feature = "metrics")]
+        #[must_use]
+        pub fn metrics(
+            mut self,
+            path: &str,
+            metrics: std::sync::Arc<crate::metrics::ClientMetrics>,
+        ) -> Self {
+            use axum::http::{header, StatusCode};
+            self.inner = self.inner.route(
+                path,
+                axum::routing::get(move || {
+                    let metrics = metrics.clone();
+                    async move {
+                        match metrics.encode() {
+                            Ok(text) => (
+                                StatusCode::OK,
+                                [(header::CONTENT_TYPE, crate::metrics::CONTENT_TYPE)],
+                                text,
+                            ),
+                            Err(e) => (
+                                StatusCode::INTERNAL_SERVER_ERROR,
+                                [(header::CONTENT_TYPE, "text/plain")],
+                                e.to_string(),
+                            ),
+                        }
+                    }
+                }),
+            );
+            self
+        }
+
+        /// Serve the registration status of `client` as JSON at `path`,
+        /// usually [`crate::status::STATUS_PATH`].
+        ///
+        /// The route is not registered with shenyu admin. With a `token`,
+        /// requests must send it as `Authorization: Bearer <token>`.
+        #[must_use]
+        pub fn status(
+            mut self,
+            path: &str,
+            client: std::sync::Arc<crate::core::ShenyuClient>,
+            token: Option<crate::secret::Secret>,
+        ) -> Self {
+            use axum::http::{header, HeaderMap, StatusCode};
+            let token = std::sync::Arc::new(token);
+            self.inner = self.inner.route(
+                path,
+                axum::routing::get(move |headers: HeaderMap| {
+                    let client = client.clone();
+                    let token = token.clone();
+                    async move {
+                        let authorization = headers
+                            .get(header::AUTHORIZATION)
+                            .and_then(|value| value.to_str().ok());
+                        let (status, body) =
+                            crate::status::respond(&client, token.as_ref().as_ref(), authorization);
+                        (
+                            StatusCode::from_u16(status).unwrap_or(StatusCode::OK),
+                            [(header::CONTENT_TYPE, "application/json")],
+                            body,
+                        )
+                    }
+                }),
+            );
+            self
+        }
+
         #[must_use]
         #[track_caller]
         pub fn merge<R>(mut self, other: ShenYuRouter<R>) -> Self
@@ -311,6 +442,128 @@ pub mod actix_web_impl {
         }
     }
 
+    /// Middleware running the request in a span carrying the trace context
+    /// forwarded by the gateway and the matched `ShenYu` rule, see
+    /// [`crate::otel`].
+    ///
+    /// Wrap the app with `actix_web::middleware::from_fn(trace_context)`.
+    /// The rule is looked up in the `web::Data<ShenYuRouter>` of the app,
+    /// when added with `App::app_data`.
+    #[cfg(feature = "otel")]
+    pub async fn trace_context(
+        request: actix_web::dev::ServiceRequest,
+        next: actix_web::middleware::Next<impl actix_web::body::MessageBody>,
+    ) -> Result<actix_web::dev::ServiceResponse<impl actix_web::body::MessageBody>, actix_web::Error>
+    {
+        use tracing::Instrument;
+
+        let parent = crate::otel::extract(
+            request
+                .headers()
+                .iter()
+                .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?))),
+        );
+        let route = request.match_pattern();
+        let uri_infos = request
+            .app_data::<actix_web::web::Data<ShenYuRouter>>()
+            .map_or(&[][..], |router| router.uri_infos.as_slice());
+        let span = crate::otel::request_span(
+            request.method().as_str(),
+            route.as_deref(),
+            uri_infos,
+            parent,
+        );
+        next.call(request).instrument(span).await
+    }
+
+    /// A resource serving `metrics` in the Prometheus text format at
+    /// `path`, to add with `App::service`.
+    ///
+    /// The resource is not registered with shenyu admin.
+    #[cfg(feature = "metrics")]
+    #[must_use]
+    pub fn metrics_resource(
+        path: &str,
+        metrics: std::sync::Arc<crate::metrics::ClientMetrics>,
+    ) -> actix_web::Resource {
+        use actix_web::{web, HttpResponse};
+        async fn serve(metrics: web::Data<crate::metrics::ClientMetrics>) -> HttpResponse {
+            match metrics.encode() {
+                Ok(text) => HttpResponse::Ok()
+                    .content_type(crate::metrics::CONTENT_TYPE)
+                    .body(text),
+                Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+            }
+        }
+        web::resource(path)
+            .app_data(web::Data::from(metrics))
+            .route(web::get().to(serve))
+    }
+
+    /// Register `client`, then take it offline on `ctrl_c`, logging the
+    /// failures. Called by [`crate::register_once`].
+    #[doc(hidden)]
+    pub fn register_until_ctrl_c(
+        client: Result<crate::core::ShenyuClient, crate::error::ShenYuError>,
+    ) {
+        let client = match client {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::error!(error = %e, "failed to create the shenyu client");
+                return;
+            }
+        };
+        match client.register() {
+            Ok(report) if !report.is_success() => {
+                tracing::warn!(?report, "registered with shenyu admin partially");
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!(error = %e, "failed to register with shenyu admin"),
+        }
+        _ = actix_web::rt::spawn(async move {
+            if actix_web::rt::signal::ctrl_c().await.is_ok() {
+                let report = client.offline_register();
+                if !report.is_success() {
+                    tracing::warn!(?report, "failed to go offline on shenyu admin");
+                }
+            }
+        });
+    }
+
+    /// A resource serving the registration status of `client` as JSON at
+    /// `path`, usually [`crate::status::STATUS_PATH`], to add with
+    /// `App::service`.
+    ///
+    /// The resource is not registered with shenyu admin. With a `token`,
+    /// requests must send it as `Authorization: Bearer <token>`.
+    #[must_use]
+    pub fn status_resource(
+        path: &str,
+        client: std::sync::Arc<crate::core::ShenyuClient>,
+        token: Option<crate::secret::Secret>,
+    ) -> actix_web::Resource {
+        use actix_web::http::{header, StatusCode};
+        use actix_web::{web, HttpRequest, HttpResponse};
+        struct Status {
+            client: std::sync::Arc<crate::core::ShenyuClient>,
+            token: Option<crate::secret::Secret>,
+        }
+        async fn serve(request: HttpRequest, status: web::Data<Status>) -> HttpResponse {
+            let authorization = request
+                .headers()
+                .get(header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok());
+            let (code, body) =
+                crate::status::respond(&status.client, status.token.as_ref(), authorization);
+            HttpResponse::build(StatusCode::from_u16(code).unwrap_or(StatusCode::OK))
+                .content_type("application/json")
+                .body(body)
+        }
+        web::resource(path)
+            .app_data(web::Data::new(Status { client, token }))
+            .route(web::get().to(serve))
+    }
+
     /// Macro to register the `ShenYu` client once.
     ///
     /// This macro ensures that the `ShenYu` client is registered only once using a `OnceLock`.
@@ -322,35 +575,37 @@ pub mod actix_web_impl {
     /// * `$config` - The configuration for the `ShenYu` client.
     /// * `$router` - The router instance.
     /// * `$port` - The port number.
+    /// * `$metrics` - Optionally, the `Arc<ClientMetrics>` recording the
+    ///   registration, with the `metrics` feature.
     #[macro_export]
     macro_rules! register_once {
-        ($config:expr, $router:expr, $port:literal) => {
+        (@register $config:expr, $router:expr, $port:literal, $setup:expr) => {
             use std::sync::OnceLock;
             use $crate::IRouter;
 
             static ONCE: OnceLock<()> = OnceLock::new();
             ONCE.get_or_init(|| {
-                let client = {
-                    let res = $crate::core::ShenyuClient::new(
-                        $config,
-                        $router.app_name(),
-                        $router.uri_infos(),
-                        $port,
-                    );
-                    let client = res.unwrap();
-                    client
-                };
-                client.register().expect("Failed to register");
-                actix_web::rt::spawn(async move {
-                    // Add shutdown hook
-                    tokio::select! {
-                        _ = actix_web::rt::signal::ctrl_c() => {
-                            client.offline_register();
-                        }
-                    }
-                });
+                let client = $crate::core::ShenyuClient::new(
+                    $config,
+                    $router.app_name(),
+                    $router.uri_infos(),
+                    $port,
+                )
+                .map($setup);
+                $crate::actix_web_impl::register_until_ctrl_c(client);
             });
         };
+        ($config:expr, $router:expr, $port:literal) => {
+            $crate::register_once!(@register $config, $router, $port, |client| client);
+        };
+        ($config:expr, $router:expr, $port:literal, $metrics:expr) => {
+            $crate::register_once!(
+                @register $config,
+                $router,
+                $port,
+                |client: $crate::core::ShenyuClient| client.metrics($metrics)
+            );
+        };
     }
 
     /// Macro to define routes for the `ShenYu` router.
@@ -413,7 +668,7 @@ mod tests_axum {
             .call()
             .unwrap();
         let res_data: Value = res.into_json().unwrap();
-        print!("res_data: {:?}", res_data);
+        print!("res_data: {res_data:?}");
         print!("res_data:token {:?}", res_data["data"]["token"]);
     }
 
@@ -428,8 +683,106 @@ mod tests_axum {
         assert!(&res.is_ok());
         let client = &mut res.unwrap();
 
-        client.register().unwrap();
-        client.offline_register();
+        _ = client.register();
+        assert_eq!(client.status().namespaces.len(), 1);
+        _ = client.offline_register();
+    }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn test_metrics_route() {
+        use crate::metrics::ClientMetrics;
+        use axum::body::Body;
+        use axum::extract::Request;
+        use axum::Router;
+        use std::sync::Arc;
+        use tower_service::Service;
+
+        let app = ShenYuRouter::<()>::new("shenyu_client_app")
+            .route("/health", "get", get(health_handler))
+            .metrics("/metrics", Arc::new(ClientMetrics::new()));
+        assert_eq!(app.uri_infos().len(), 1);
+
+        let mut router: Router = app.into();
+        let request = Request::get("/metrics").body(Body::empty()).unwrap();
+        let response = router.call(request).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.headers()["content-type"],
+            crate::metrics::CONTENT_TYPE
+        );
+    }
+
+    #[tokio::test]
+    async fn test_status_route() {
+        use crate::config::HostMode;
+        use crate::secret::Secret;
+        use crate::status::STATUS_PATH;
+        use axum::body::{to_bytes, Body};
+        use axum::extract::Request;
+        use axum::Router;
+        use std::sync::Arc;
+        use tower_service::Service;
+
+        let app = ShenYuRouter::<()>::new("shenyu_client_app").route(
+            "/health",
+            "get",
+            get(health_handler),
+        );
+        let config = ShenYuConfig::builder()
+            .servers("http://127.0.0.1:1")
+            .register_prop("username", "admin")
+            .register_prop("password", "123456")
+            .host("10.0.0.1")
+            .host_mode(HostMode::Literal)
+            .context_path("/app")
+            .build();
+        let client = ShenyuClient::new(config, app.app_name(), app.uri_infos(), 3000).unwrap();
+        let app = app.status(STATUS_PATH, Arc::new(client), Some(Secret::new("s3cret")));
+        assert_eq!(app.uri_infos().len(), 1);
+
+        let mut router: Router = app.into();
+        let request = Request::get(STATUS_PATH).body(Body::empty()).unwrap();
+        let response = router.call(request).await.unwrap();
+        assert_eq!(response.status(), 401);
+
+        let request = Request::get(STATUS_PATH)
+            .header("authorization", "Bearer s3cret")
+            .body(Body::empty())
+            .unwrap();
+        let response = router.call(request).await.unwrap();
+        assert_eq!(response.status(), 200);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let status: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(status["appName"], "shenyu_client_app");
+        assert_eq!(status["host"], "10.0.0.1");
+        assert_eq!(status["uriInfos"][0]["path"], "/health");
+        assert_eq!(status["namespaces"][0]["state"], "unregistered");
+    }
+
+    #[cfg(feature = "otel")]
+    #[tokio::test]
+    async fn test_trace_context() {
+        use axum::body::Body;
+        use axum::extract::Request;
+        use axum::Router;
+        use tower_service::Service;
+
+        let app = ShenYuRouter::<()>::new("shenyu_client_app")
+            .route("/users", "post", post(create_user_handler))
+            .trace_context();
+        assert_eq!(app.uri_infos().len(), 1);
+
+        let mut router: Router = app.into();
+        let request = Request::post("/users")
+            .header(
+                "traceparent",
+                "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            )
+            .body(Body::empty())
+            .unwrap();
+        let response = router.call(request).await.unwrap();
+        assert_eq!(response.status(), 200);
     }
 
     #[test]
@@ -462,7 +815,37 @@ mod tests_actix_web {
         assert!(&res.is_ok());
         let client = &mut res.unwrap();
 
-        client.register().unwrap();
-        client.offline_register();
+        _ = client.register();
+        assert_eq!(client.status().namespaces.len(), 1);
+        _ = client.offline_register();
+    }
+
+    #[actix_web::test]
+    async fn test_status_resource() {
+        use super::actix_web_impl::status_resource;
+        use crate::config::HostMode;
+        use crate::status::STATUS_PATH;
+        use actix_web::{test, App};
+        use std::sync::Arc;
+
+        let config = ShenYuConfig::builder()
+            .servers("http://127.0.0.1:1")
+            .register_prop("username", "admin")
+            .register_prop("password", "123456")
+            .host("10.0.0.1")
+            .host_mode(HostMode::Literal)
+            .context_path("/app")
+            .build();
+        let client = ShenyuClient::new(config, "shenyu_client_app", &[], 9527).unwrap();
+        let app = test::init_service(App::new().service(status_resource(
+            STATUS_PATH,
+            Arc::new(client),
+            None,
+        )))
+        .await;
+        let request = test::TestRequest::get().uri(STATUS_PATH).to_request();
+        let status: serde_json::Value = test::call_and_read_body_json(&app, request).await;
+        assert_eq!(status["appName"], "shenyu_client_app");
+        assert_eq!(status["port"], 9527);
     }
 }
@@ -0,0 +1,85 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Callbacks on the registration with shenyu admin.
+//!
+//! Register a [`RegistrationListener`] with
+//! [`crate::core::ShenyuClient::listener`] to flip a readiness flag, alert
+//! when going offline fails, or record audit events. Callbacks run on the
+//! thread sending the requests, so they should return quickly. No lock of
+//! the client is held meanwhile, so they may call it back, e.g. to reload.
+
+use crate::error::ShenYuError;
+use crate::model::{DiscoveryConfigRegistration, MetadataRegistration, UriRegistration};
+
+/// What an event is about: the target and admin server, and the payload
+/// sent.
+#[derive(Debug, Clone, Copy)]
+pub struct AdminEvent<'a, P> {
+    /// The name of the target, see [`crate::config::ShenYuConfig::targets`].
+    pub target: &'a str,
+    /// The base url of the admin server.
+    pub server: &'a str,
+    /// The payload sent to the admin server.
+    pub payload: &'a P,
+}
+
+/// A login to an admin server, as its username.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Login {
+    /// The username logged in with.
+    pub username: String,
+}
+
+/// Callbacks on the registration lifecycle, all doing nothing by default.
+pub trait RegistrationListener: Send + Sync {
+    /// An admin token was obtained.
+    fn on_token_acquired(&self, _event: &AdminEvent<'_, Login>) {}
+
+    /// Logging in to an admin server failed.
+    fn on_token_failed(&self, _event: &AdminEvent<'_, Login>, _error: &ShenYuError) {}
+
+    /// The metadata of a route was registered.
+    fn on_route_registered(&self, _event: &AdminEvent<'_, MetadataRegistration>) {}
+
+    /// Registering the metadata of a route failed.
+    fn on_route_failed(&self, _event: &AdminEvent<'_, MetadataRegistration>, _error: &ShenYuError) {
+    }
+
+    /// The uri of the instance was registered.
+    fn on_uri_registered(&self, _event: &AdminEvent<'_, UriRegistration>) {}
+
+    /// The discovery config was registered.
+    fn on_discovery_registered(&self, _event: &AdminEvent<'_, DiscoveryConfigRegistration>) {}
+
+    /// A heartbeat to a server and namespace failed, the first of a row of
+    /// failures.
+    fn on_heartbeat_lost(&self, _event: &AdminEvent<'_, UriRegistration>, _error: &ShenYuError) {}
+
+    /// A heartbeat succeeded after the previous one to the same server and
+    /// namespace failed.
+    fn on_heartbeat_recovered(&self, _event: &AdminEvent<'_, UriRegistration>) {}
+
+    /// Taking the instance offline from an admin server and namespace
+    /// ended, with its result.
+    fn on_offline_completed(
+        &self,
+        _event: &AdminEvent<'_, UriRegistration>,
+        _result: Result<(), &ShenYuError>,
+    ) {
+    }
+}
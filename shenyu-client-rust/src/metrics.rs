@@ -0,0 +1,251 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Prometheus metrics of the registration with shenyu admin.
+//!
+//! Enabled by the `metrics` feature. Attach a [`ClientMetrics`] to the
+//! client with [`ShenyuClient::metrics`] and serve
+//! [`ClientMetrics::encode`], e.g. through the axum or actix integrations.
+//!
+//! [`ShenyuClient::metrics`]: crate::core::ShenyuClient::metrics
+
+use crate::core::{Call, Operation};
+use crate::error::ShenYuError;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use std::fmt::{Debug, Formatter};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The content type of [`ClientMetrics::encode`].
+pub const CONTENT_TYPE: &str = prometheus::TEXT_FORMAT;
+
+/// Counters, latencies and gauges of the requests to shenyu admin, labelled
+/// by admin server and namespace.
+///
+/// * `shenyu_client_registration_attempts_total`, by `event_type` (uri,
+///   metadata or discovery config) and `outcome`;
+/// * `shenyu_client_offline_attempts_total`,
+///   `shenyu_client_token_attempts_total` and
+///   `shenyu_client_heartbeat_attempts_total`, by `outcome`;
+/// * `shenyu_client_admin_request_duration_seconds`, by `operation`;
+/// * `shenyu_client_routes_registered`, the routes whose latest metadata
+///   was not failed, as in
+///   [`ShenyuClient::status`](crate::core::ShenyuClient::status);
+/// * `shenyu_client_last_success_timestamp_seconds`.
+///
+/// The outcome is `success`, `rejected` by shenyu admin, or `error` when no
+/// answer was read. Logins have an empty namespace.
+pub struct ClientMetrics {
+    registry: Registry,
+    registrations: IntCounterVec,
+    offlines: IntCounterVec,
+    tokens: IntCounterVec,
+    heartbeats: IntCounterVec,
+    latency: HistogramVec,
+    routes: IntGaugeVec,
+    last_success: IntGaugeVec,
+}
+
+impl ClientMetrics {
+    /// Create the metrics in a registry of their own.
+    #[must_use]
+    pub fn new() -> Self {
+        let registry = Registry::new();
+        let counter = |name: &str, help: &str, labels: &[&str]| {
+            let counter = IntCounterVec::new(Opts::new(name, help), labels)
+                .expect("valid counter definition");
+            registry
+                .register(Box::new(counter.clone()))
+                .expect("unique metric name");
+            counter
+        };
+        let registrations = counter(
+            "shenyu_client_registration_attempts_total",
+            "Registration requests sent to shenyu admin.",
+            &["server", "namespace", "event_type", "outcome"],
+        );
+        let offlines = counter(
+            "shenyu_client_offline_attempts_total",
+            "Offline requests sent to shenyu admin.",
+            &["server", "namespace", "outcome"],
+        );
+        let tokens = counter(
+            "shenyu_client_token_attempts_total",
+            "Logins to shenyu admin.",
+            &["server", "namespace", "outcome"],
+        );
+        let heartbeats = counter(
+            "shenyu_client_heartbeat_attempts_total",
+            "Heartbeats sent to shenyu admin.",
+            &["server", "namespace", "outcome"],
+        );
+        let gauge = |name: &str, help: &str| {
+            let gauge = IntGaugeVec::new(Opts::new(name, help), &["server", "namespace"])
+                .expect("valid gauge definition");
+            registry
+                .register(Box::new(gauge.clone()))
+                .expect("unique metric name");
+            gauge
+        };
+        let routes = gauge(
+            "shenyu_client_routes_registered",
+            "Routes whose metadata shenyu admin accepted.",
+        );
+        let last_success = gauge(
+            "shenyu_client_last_success_timestamp_seconds",
+            "Unix time of the last request shenyu admin accepted.",
+        );
+        let latency = HistogramVec::new(
+            HistogramOpts::new(
+                "shenyu_client_admin_request_duration_seconds",
+                "Latency of the requests to shenyu admin.",
+            ),
+            &["server", "namespace", "operation"],
+        )
+        .expect("valid histogram definition");
+        registry
+            .register(Box::new(latency.clone()))
+            .expect("unique metric name");
+        ClientMetrics {
+            registry,
+            registrations,
+            offlines,
+            tokens,
+            heartbeats,
+            latency,
+            routes,
+            last_success,
+        }
+    }
+
+    /// The registry holding the metrics, to gather them along with others.
+    #[must_use]
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// The metrics in the Prometheus text format, see [`CONTENT_TYPE`].
+    pub fn encode(&self) -> Result<String, ShenYuError> {
+        let mut buffer = vec![];
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .map_err(|e| ShenYuError::serialization("encode the metrics", e))?;
+        String::from_utf8(buffer).map_err(|e| ShenYuError::serialization("encode the metrics", e))
+    }
+
+    /// Record a request to `server` for `call`.
+    pub(crate) fn observe(&self, call: &Call<'_>, server: &str, latency: Duration, outcome: &str) {
+        let namespace = call.namespace;
+        let operation = call.operation.as_str();
+        let attempts = match call.operation {
+            Operation::Login => &self.tokens,
+            Operation::Offline => &self.offlines,
+            Operation::Heartbeat => &self.heartbeats,
+            Operation::RegisterUri
+            | Operation::RegisterMetadata
+            | Operation::RegisterDiscoveryConfig => {
+                self.registrations
+                    .with_label_values(&[server, namespace, operation, outcome])
+                    .inc();
+                return self.observe_latency(call, server, latency, outcome);
+            }
+        };
+        attempts
+            .with_label_values(&[server, namespace, outcome])
+            .inc();
+        self.observe_latency(call, server, latency, outcome);
+    }
+
+    fn observe_latency(&self, call: &Call<'_>, server: &str, latency: Duration, outcome: &str) {
+        let namespace = call.namespace;
+        self.latency
+            .with_label_values(&[server, namespace, call.operation.as_str()])
+            .observe(latency.as_secs_f64());
+        if outcome != "success" {
+            return;
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |now| i64::try_from(now.as_secs()).unwrap_or(i64::MAX));
+        self.last_success
+            .with_label_values(&[server, namespace])
+            .set(now);
+    }
+
+    /// Set the routes registered with `server` in `namespace`.
+    pub(crate) fn set_routes(&self, server: &str, namespace: &str, routes: usize) {
+        self.routes
+            .with_label_values(&[server, namespace])
+            .set(i64::try_from(routes).unwrap_or(i64::MAX));
+    }
+}
+
+impl Default for ClientMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for ClientMetrics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientMetrics").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observe() {
+        let metrics = ClientMetrics::new();
+        let server = "http://127.0.0.1:9095";
+        for route in ["/users", "/orders", "/users"] {
+            let call = Call {
+                operation: Operation::RegisterMetadata,
+                namespace: "dev",
+                route: Some(route),
+            };
+            metrics.observe(&call, server, Duration::from_millis(5), "success");
+        }
+        let login = Call {
+            operation: Operation::Login,
+            namespace: "",
+            route: None,
+        };
+        metrics.observe(&login, server, Duration::from_millis(5), "rejected");
+        metrics.set_routes(server, "dev", 2);
+
+        let text = metrics.encode().unwrap();
+        assert!(text.contains(
+            r#"shenyu_client_registration_attempts_total{event_type="register_metadata",namespace="dev",outcome="success",server="http://127.0.0.1:9095"} 3"#
+        ));
+        assert!(text.contains(
+            r#"shenyu_client_routes_registered{namespace="dev",server="http://127.0.0.1:9095"} 2"#
+        ));
+        assert!(text.contains(
+            r#"shenyu_client_token_attempts_total{namespace="",outcome="rejected",server="http://127.0.0.1:9095"} 1"#
+        ));
+        assert!(text.contains("shenyu_client_admin_request_duration_seconds_bucket"));
+
+        metrics.set_routes(server, "dev", 0);
+        assert!(metrics.encode().unwrap().contains(
+            r#"shenyu_client_routes_registered{namespace="dev",server="http://127.0.0.1:9095"} 0"#
+        ));
+    }
+}
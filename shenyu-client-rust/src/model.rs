@@ -15,6 +15,8 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
 /**
@@ -36,7 +38,8 @@ use std::fmt::{Display, Formatter};
 }
 */
 #[allow(clippy::doc_markdown)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct UriInfo {
     /// The shenyu rule path.
     pub path: String,
@@ -49,7 +52,7 @@ pub struct UriInfo {
 }
 
 /// The enum Event type.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum EventType {
     ///Register event type.
     REGISTER,
@@ -78,3 +81,113 @@ impl Display for EventType {
         }
     }
 }
+
+impl Serialize for EventType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for EventType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match String::deserialize(deserializer)?.as_str() {
+            "REGISTER" => Ok(EventType::REGISTER),
+            "UPDATED" => Ok(EventType::UPDATED),
+            "DELETED" => Ok(EventType::DELETED),
+            "IGNORED" => Ok(EventType::IGNORED),
+            "OFFLINE" => Ok(EventType::OFFLINE),
+            other => Err(serde::de::Error::unknown_variant(
+                other,
+                &["REGISTER", "UPDATED", "DELETED", "IGNORED", "OFFLINE"],
+            )),
+        }
+    }
+}
+
+/// The uri of the instance, sent to register or take it offline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UriRegistration {
+    /// The app name.
+    pub app_name: String,
+    /// The context path of the app.
+    pub context_path: String,
+    /// The protocol, the rpc type.
+    pub protocol: String,
+    /// The rpc type, not sent when going offline.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rpc_type: Option<String>,
+    /// The advertised host.
+    pub host: String,
+    /// The advertised port.
+    pub port: u16,
+    /// The admin namespace, not sent to admins predating namespaces.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace_id: Option<String>,
+    /// `REGISTER` or `OFFLINE`.
+    pub event_type: EventType,
+}
+
+/// The metadata of a route, sent to register it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataRegistration {
+    /// The app name.
+    pub app_name: String,
+    /// The context path of the app.
+    pub context_path: String,
+    /// The full path of the route.
+    pub path: String,
+    /// The description of the path.
+    pub path_desc: String,
+    /// The rpc type.
+    pub rpc_type: String,
+    /// The full rule name of the route.
+    pub rule_name: String,
+    /// The service name.
+    pub service_name: String,
+    /// The handler method name.
+    pub method_name: String,
+    /// The parameter types of the handler.
+    pub parameter_types: String,
+    /// Extra rpc information.
+    pub rpc_ext: String,
+    /// The advertised host.
+    pub host: String,
+    /// The advertised port.
+    pub port: u16,
+    /// The admin namespace, not sent to admins predating namespaces.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace_id: Option<String>,
+    /// Whether the route is enabled.
+    pub enabled: bool,
+    /// Whether to register the metadata itself.
+    pub register_meta_data: String,
+    /// The plugins of the route.
+    pub plugin_names: Vec<String>,
+}
+
+/// The discovery config of the app, sent to register it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveryConfigRegistration {
+    /// The discovery config name.
+    pub name: String,
+    /// The selector, the context path of the app.
+    pub selector_name: String,
+    /// The selector handler.
+    pub handler: String,
+    /// The path listened to in the registry.
+    pub listener_node: String,
+    /// The registry servers.
+    pub server_list: String,
+    /// The registry properties.
+    pub props: HashMap<String, String>,
+    /// The registry type.
+    pub discovery_type: String,
+    /// The plugin of the selector.
+    pub plugin_name: String,
+    /// The admin namespace, not sent to admins predating namespaces.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub namespace_id: Option<String>,
+}
@@ -0,0 +1,308 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! OpenTelemetry context propagation for requests forwarded by the gateway.
+//!
+//! Enabled by the `otel` feature. The trace context the gateway forwards,
+//! as W3C `traceparent`/`tracestate` or B3 (`b3` or `X-B3-*`) headers,
+//! becomes the parent of a `shenyu.request` span around the handler, which
+//! also records the `ShenYu` rule the request matched. The span joins the
+//! distributed trace when the subscriber has a `tracing-opentelemetry`
+//! layer; its `trace_id` field correlates logs either way.
+//!
+//! See `ShenYuRouter::trace_context` for axum and `trace_context` for
+//! actix-web.
+
+use crate::model::UriInfo;
+use opentelemetry::propagation::{
+    text_map_propagator::FieldIter, Extractor, Injector, TextMapCompositePropagator,
+    TextMapPropagator,
+};
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+use opentelemetry::Context;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use tracing::{field, info_span, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+const B3_SINGLE_HEADER: &str = "b3";
+const B3_TRACE_ID_HEADER: &str = "x-b3-traceid";
+const B3_SPAN_ID_HEADER: &str = "x-b3-spanid";
+const B3_SAMPLED_HEADER: &str = "x-b3-sampled";
+const B3_DEBUG_FLAG_HEADER: &str = "x-b3-flags";
+
+/// Propagates the B3 trace context, reading both the single `b3` header
+/// and the `X-B3-*` headers, and writing the single header.
+#[derive(Debug)]
+pub struct B3Propagator {
+    fields: [String; 5],
+}
+
+impl B3Propagator {
+    /// Create a B3 propagator.
+    #[must_use]
+    pub fn new() -> Self {
+        B3Propagator {
+            fields: [
+                B3_SINGLE_HEADER,
+                B3_TRACE_ID_HEADER,
+                B3_SPAN_ID_HEADER,
+                B3_SAMPLED_HEADER,
+                B3_DEBUG_FLAG_HEADER,
+            ]
+            .map(ToString::to_string),
+        }
+    }
+
+    fn extract_single(value: &str) -> Option<SpanContext> {
+        let mut parts = value.split('-');
+        let trace_id = parts.next()?;
+        let span_id = parts.next()?;
+        let sampled = parts.next();
+        span_context(trace_id, span_id, sampled)
+    }
+
+    fn extract_multi(extractor: &dyn Extractor) -> Option<SpanContext> {
+        let sampled = match extractor.get(B3_DEBUG_FLAG_HEADER) {
+            Some("1") => Some("d"),
+            _ => extractor.get(B3_SAMPLED_HEADER),
+        };
+        span_context(
+            extractor.get(B3_TRACE_ID_HEADER)?,
+            extractor.get(B3_SPAN_ID_HEADER)?,
+            sampled,
+        )
+    }
+}
+
+impl Default for B3Propagator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextMapPropagator for B3Propagator {
+    fn inject_context(&self, cx: &Context, injector: &mut dyn Injector) {
+        let span = cx.span();
+        let span_context = span.span_context();
+        if span_context.is_valid() {
+            let sampled = if span_context.is_sampled() { "1" } else { "0" };
+            injector.set(
+                B3_SINGLE_HEADER,
+                format!(
+                    "{}-{}-{sampled}",
+                    span_context.trace_id(),
+                    span_context.span_id()
+                ),
+            );
+        }
+    }
+
+    fn extract_with_context(&self, cx: &Context, extractor: &dyn Extractor) -> Context {
+        let span_context = match extractor.get(B3_SINGLE_HEADER) {
+            Some(value) => Self::extract_single(value),
+            None => Self::extract_multi(extractor),
+        };
+        match span_context {
+            Some(span_context) => cx.with_remote_span_context(span_context),
+            None => cx.clone(),
+        }
+    }
+
+    fn fields(&self) -> FieldIter<'_> {
+        FieldIter::new(&self.fields)
+    }
+}
+
+/// A remote span context from B3 ids, 64-bit trace ids left-padded.
+fn span_context(trace_id: &str, span_id: &str, sampled: Option<&str>) -> Option<SpanContext> {
+    let trace_id = match trace_id.len() {
+        16 | 32 => TraceId::from_hex(trace_id).ok()?,
+        _ => return None,
+    };
+    let span_id = match span_id.len() {
+        16 => SpanId::from_hex(span_id).ok()?,
+        _ => return None,
+    };
+    let flags = match sampled {
+        Some("1" | "d" | "true") => TraceFlags::SAMPLED,
+        _ => TraceFlags::default(),
+    };
+    let span_context = SpanContext::new(trace_id, span_id, flags, true, TraceState::default());
+    span_context.is_valid().then_some(span_context)
+}
+
+/// The propagator reading gateway-forwarded requests: W3C trace context,
+/// else B3.
+fn propagator() -> &'static TextMapCompositePropagator {
+    static PROPAGATOR: OnceLock<TextMapCompositePropagator> = OnceLock::new();
+    // Applied in order, the W3C context overrides a B3 one.
+    PROPAGATOR.get_or_init(|| {
+        TextMapCompositePropagator::new(vec![
+            Box::new(B3Propagator::new()),
+            Box::new(TraceContextPropagator::new()),
+        ])
+    })
+}
+
+/// Extract the trace context from request `headers`.
+pub fn extract<'a, I>(headers: I) -> Context
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    let propagator = propagator();
+    let headers: HashMap<String, String> = headers
+        .into_iter()
+        .map(|(name, value)| (name.to_ascii_lowercase(), value.to_string()))
+        .filter(|(name, _)| propagator.fields().any(|field| field == name))
+        .collect();
+    propagator.extract(&headers)
+}
+
+/// The [`UriInfo`] of a `method` request to the route `route`, the path
+/// pattern the request matched, allowing for the prefix of a nested router
+/// and the rest of the path handled by a nested service.
+#[must_use]
+pub fn matched_rule<'a>(
+    uri_infos: &'a [UriInfo],
+    method: &str,
+    route: &str,
+) -> Option<&'a UriInfo> {
+    let candidates = || {
+        uri_infos
+            .iter()
+            .filter(|info| info.method_name.eq_ignore_ascii_case(method))
+    };
+    candidates()
+        .find(|info| info.path == route)
+        .or_else(|| candidates().find(|info| nests(route, &info.path)))
+}
+
+/// Whether `route` is `path` after a router prefix, or followed by the
+/// rest of the path of a nested service, split at `/`.
+fn nests(route: &str, path: &str) -> bool {
+    !path.is_empty()
+        && route.match_indices(path).any(|(start, _)| {
+            let (prefix, rest) = (&route[..start], &route[start + path.len()..]);
+            (prefix.is_empty() || (prefix.starts_with('/') && path.starts_with('/')))
+                && (rest.is_empty() || rest.starts_with('/'))
+        })
+}
+
+/// The span of a request matching `route`, child of `parent`, recording
+/// the `ShenYu` rule of the route.
+#[must_use]
+pub fn request_span(
+    method: &str,
+    route: Option<&str>,
+    uri_infos: &[UriInfo],
+    parent: Context,
+) -> Span {
+    let rule = route.and_then(|route| matched_rule(uri_infos, method, route));
+    let span = info_span!(
+        "shenyu.request",
+        otel.name = format!("{method} {}", route.unwrap_or_default()),
+        http.request.method = method,
+        http.route = route,
+        shenyu.rule_name = rule.map(|rule| rule.rule_name.as_str()),
+        shenyu.path = rule.map(|rule| rule.path.as_str()),
+        shenyu.method_name = rule.map(|rule| rule.method_name.as_str()),
+        shenyu.service_name = rule.and_then(|rule| rule.service_name.as_deref()),
+        trace_id = field::Empty,
+    );
+    let remote = parent.span().span_context().clone();
+    if remote.is_valid() {
+        _ = span.record("trace_id", field::display(remote.trace_id()));
+    }
+    span.set_parent(parent);
+    span
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract() {
+        let w3c = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let cx = extract([("traceparent", w3c)]);
+        let span = cx.span();
+        assert_eq!(
+            span.span_context().trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        assert!(span.span_context().is_sampled());
+
+        let cx = extract([
+            ("X-B3-TraceId", "80f198ee56343ba864fe8b2a57d3eff7"),
+            ("X-B3-SpanId", "e457b5a2e4d86bd1"),
+            ("X-B3-Sampled", "1"),
+        ]);
+        assert_eq!(
+            cx.span().span_context().span_id().to_string(),
+            "e457b5a2e4d86bd1"
+        );
+
+        let cx = extract([
+            ("b3", "a3ce929d0e0e4736-00f067aa0ba902b7-0"),
+            ("traceparent", w3c),
+        ]);
+        assert_eq!(
+            cx.span().span_context().trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+        let cx = extract([("b3", "a3ce929d0e0e4736-00f067aa0ba902b7-0")]);
+        assert_eq!(
+            cx.span().span_context().trace_id().to_string(),
+            "0000000000000000a3ce929d0e0e4736"
+        );
+        assert!(!cx.span().span_context().is_sampled());
+
+        assert!(!extract([("b3", "garbage")])
+            .span()
+            .span_context()
+            .is_valid());
+    }
+
+    #[test]
+    fn test_matched_rule() {
+        let info = |path: &str, method: &str| UriInfo {
+            path: path.to_string(),
+            rule_name: format!("{method} {path}"),
+            service_name: None,
+            method_name: method.to_string(),
+        };
+        let uri_infos = vec![
+            info("/users", "get"),
+            info("/users", "post"),
+            info("/static", "get"),
+        ];
+        let rule = |method, route| {
+            matched_rule(&uri_infos, method, route).map(|info| info.rule_name.as_str())
+        };
+        assert_eq!(rule("GET", "/users"), Some("get /users"));
+        assert_eq!(rule("POST", "/users"), Some("post /users"));
+        assert_eq!(rule("POST", "/api/users"), Some("post /users"));
+        assert_eq!(rule("GET", "/static/{*rest}"), Some("get /static"));
+        assert_eq!(rule("DELETE", "/users"), None);
+        assert_eq!(rule("GET", "/usersx"), None);
+        assert_eq!(rule("GET", "/api/usersx"), None);
+        assert_eq!(rule("GET", "/statics"), None);
+        assert_eq!(rule("GET", "/health"), None);
+    }
+}
@@ -0,0 +1,259 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use crate::error::ShenYuError;
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::de::{Error as _, MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+use std::fmt::{Debug, Display, Formatter};
+use std::path::PathBuf;
+use zeroize::Zeroize;
+
+/// What is printed in place of a secret.
+pub const REDACTED: &str = "******";
+
+/// A credential, such as the shenyu admin password.
+///
+/// It is redacted in `Debug` and `Display` output, and its memory is zeroed
+/// on drop. Only this final value is zeroed: the copies made while loading
+/// a config, such as the file text, its parsed YAML values, interpolated
+/// placeholders, `SHENYU_*` overrides and format conversions, are plain
+/// strings dropped as they are.
+///
+/// In config files it is written either as a plain value or as a
+/// reference to where the value is read from at load time:
+///
+/// ```yaml
+/// password: "123456"
+/// password: { env: SHENYU_ADMIN_PASSWORD }
+/// password: { file: /run/secrets/shenyu-admin-password }
+/// ```
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct Secret {
+    value: String,
+    source: SecretSource,
+}
+
+/// Where a [`Secret`] was read from, kept so serializing a config writes
+/// back the reference rather than the value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+enum SecretSource {
+    #[default]
+    Literal,
+    Env(String),
+    File(PathBuf),
+}
+
+impl Secret {
+    /// A secret given inline.
+    pub fn new<S: Into<String>>(value: S) -> Self {
+        Secret {
+            value: value.into(),
+            source: SecretSource::Literal,
+        }
+    }
+
+    /// A secret read from the environment variable `name`.
+    pub fn from_env(name: &str) -> Result<Self, ShenYuError> {
+        let value = std::env::var(name)
+            .map_err(|e| ShenYuError::config(format!("read secret from env {name}"), e))?;
+        Ok(Secret {
+            value,
+            source: SecretSource::Env(name.to_string()),
+        })
+    }
+
+    /// A secret read from a file, without its trailing newline.
+    pub fn from_file<P: Into<PathBuf>>(path: P) -> Result<Self, ShenYuError> {
+        let path = path.into();
+        let mut value = std::fs::read_to_string(&path)
+            .map_err(|e| ShenYuError::config(format!("read secret from {}", path.display()), e))?;
+        let len = value.trim_end_matches(['\r', '\n']).len();
+        value.truncate(len);
+        Ok(Secret {
+            value,
+            source: SecretSource::File(path),
+        })
+    }
+
+    /// The secret value, to be used only where it is sent.
+    #[must_use]
+    pub fn expose(&self) -> &str {
+        &self.value
+    }
+
+    /// Whether the secret value is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.value.zeroize();
+    }
+}
+
+impl Debug for Secret {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Secret({REDACTED})")
+    }
+}
+
+impl Display for Secret {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match &self.source {
+            SecretSource::Literal => serializer.serialize_str(&self.value),
+            SecretSource::Env(name) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("env", name)?;
+                map.end()
+            }
+            SecretSource::File(path) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("file", path)?;
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(SecretVisitor)
+    }
+}
+
+struct SecretVisitor;
+
+impl<'de> Visitor<'de> for SecretVisitor {
+    type Value = Secret;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("a secret value, `{ env: NAME }` or `{ file: PATH }`")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Secret, E> {
+        Ok(Secret::new(v))
+    }
+
+    fn visit_string<E: serde::de::Error>(self, v: String) -> Result<Secret, E> {
+        Ok(Secret::new(v))
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Secret, E> {
+        Ok(Secret::new(v.to_string()))
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Secret, E> {
+        Ok(Secret::new(v.to_string()))
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, v: f64) -> Result<Secret, E> {
+        Ok(Secret::new(v.to_string()))
+    }
+
+    fn visit_bool<E: serde::de::Error>(self, v: bool) -> Result<Secret, E> {
+        Ok(Secret::new(v.to_string()))
+    }
+
+    fn visit_unit<E: serde::de::Error>(self) -> Result<Secret, E> {
+        Ok(Secret::default())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Secret, A::Error> {
+        let Some((key, value)) = map.next_entry::<String, String>()? else {
+            return Err(A::Error::custom("expected `env` or `file`"));
+        };
+        if map.next_key::<String>()?.is_some() {
+            return Err(A::Error::custom("expected only one of `env` or `file`"));
+        }
+        match key.as_str() {
+            "env" => Secret::from_env(&value).map_err(A::Error::custom),
+            "file" => Secret::from_file(value).map_err(A::Error::custom),
+            other => Err(A::Error::unknown_field(other, &["env", "file"])),
+        }
+    }
+}
+
+impl JsonSchema for Secret {
+    fn schema_name() -> Cow<'static, str> {
+        "Secret".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "A secret value, or where to read it from.",
+            "oneOf": [
+                { "type": ["string", "number"] },
+                {
+                    "type": "object",
+                    "properties": { "env": { "type": "string" } },
+                    "required": ["env"],
+                    "additionalProperties": false
+                },
+                {
+                    "type": "object",
+                    "properties": { "file": { "type": "string" } },
+                    "required": ["file"],
+                    "additionalProperties": false
+                }
+            ]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacted() {
+        let secret = Secret::new("123456");
+        assert_eq!(format!("{secret:?}"), "Secret(******)");
+        assert_eq!(secret.to_string(), REDACTED);
+        assert_eq!(secret.expose(), "123456");
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let secret: Secret = serde_yaml::from_str("123456").unwrap();
+        assert_eq!(secret.expose(), "123456");
+
+        let path = std::env::temp_dir().join(format!("shenyu-secret-{}", std::process::id()));
+        std::fs::write(&path, "s3cret\n").unwrap();
+        let yaml = format!("file: {}", path.display());
+        let secret: Secret = serde_yaml::from_str(&yaml).unwrap();
+        _ = std::fs::remove_file(&path);
+        assert_eq!(secret.expose(), "s3cret");
+        assert_eq!(serde_yaml::to_string(&secret).unwrap(), format!("{yaml}\n"));
+
+        let secret: Secret = serde_yaml::from_str("env: PATH").unwrap();
+        assert!(!secret.is_empty());
+        assert!(serde_yaml::from_str::<Secret>("env: SHENYU_SECRET_UNSET").is_err());
+        assert!(serde_yaml::from_str::<Secret>("vault: x").is_err());
+    }
+}
@@ -0,0 +1,254 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Resolution of the `register.servers` entries into admin urls.
+//!
+//! An entry is either an admin url or a source listing them:
+//!
+//! * `srv+http://_shenyu-admin._tcp.example.com`, or `srv+https://`, a DNS
+//!   SRV record whose targets are the admin servers;
+//! * `file:///etc/shenyu/admins.txt`, a file of admin urls;
+//! * `list+http://config.example.com/admins`, or `list+https://`, a url
+//!   answering with admin urls.
+//!
+//! Lists hold urls separated by commas or new lines, `#` starting a comment,
+//! or a JSON array of urls.
+
+use crate::config::check_admin_url;
+use crate::transport::Transport;
+use hickory_resolver::Resolver;
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use tracing::warn;
+
+/// Where admin urls come from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ServerSource {
+    /// An admin url.
+    Url(String),
+    /// A DNS SRV name, and the scheme of the urls it resolves to.
+    Srv { scheme: String, name: String },
+    /// A file listing admin urls.
+    File(String),
+    /// A url answering with admin urls.
+    List(String),
+}
+
+impl ServerSource {
+    /// Parse one entry of `register.servers`.
+    pub(crate) fn parse(entry: &str) -> Self {
+        if let Some((scheme, name)) = entry
+            .strip_prefix("srv+")
+            .and_then(|rest| rest.split_once("://"))
+        {
+            return ServerSource::Srv {
+                scheme: scheme.to_string(),
+                name: name.trim_end_matches('/').to_string(),
+            };
+        }
+        if let Some(path) = entry.strip_prefix("file://") {
+            return ServerSource::File(path.to_string());
+        }
+        if let Some(url) = entry.strip_prefix("list+") {
+            return ServerSource::List(url.to_string());
+        }
+        ServerSource::Url(entry.to_string())
+    }
+
+    /// Whether the urls of this source can change over time.
+    pub(crate) fn is_dynamic(&self) -> bool {
+        !matches!(self, ServerSource::Url(_))
+    }
+}
+
+impl Display for ServerSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServerSource::Url(url) => f.write_str(url),
+            ServerSource::Srv { scheme, name } => write!(f, "srv+{scheme}://{name}"),
+            ServerSource::File(path) => write!(f, "file://{path}"),
+            ServerSource::List(url) => write!(f, "list+{url}"),
+        }
+    }
+}
+
+/// The entries of `register.servers`.
+pub(crate) fn sources(servers: &str) -> Vec<ServerSource> {
+    servers
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(ServerSource::parse)
+        .collect()
+}
+
+/// Resolve `servers` into admin urls, in order and without duplicates.
+///
+/// A source that fails to resolve is an error, so a transient failure
+/// never empties the list in use. The urls a dynamic source lists are
+/// checked like the `servers` entries, and the invalid ones logged and
+/// dropped.
+pub(crate) fn resolve(servers: &str, transport: &Transport) -> Result<Vec<String>, String> {
+    let mut urls: Vec<String> = vec![];
+    for source in sources(servers) {
+        let resolved = match &source {
+            ServerSource::Url(url) => vec![url.clone()],
+            ServerSource::Srv { scheme, name } => resolve_srv(scheme, name)?,
+            ServerSource::File(path) => parse_list(
+                &std::fs::read_to_string(path)
+                    .map_err(|e| format!("failed to read admin list {path}: {e}"))?,
+            ),
+            ServerSource::List(url) => {
+                let response = transport
+                    .agent(url)
+                    .get(url)
+                    .call()
+                    .map_err(|e| format!("failed to fetch admin list {url}: {e}"))?;
+                parse_list(
+                    &response
+                        .into_string()
+                        .map_err(|e| format!("failed to read admin list {url}: {e}"))?,
+                )
+            }
+        };
+        let resolved: Vec<String> = if source.is_dynamic() {
+            resolved
+                .into_iter()
+                .filter(|url| match check_admin_url(url) {
+                    Ok(()) => true,
+                    Err(e) => {
+                        warn!(%source, url, error = %e, "ignoring an invalid admin url");
+                        false
+                    }
+                })
+                .collect()
+        } else {
+            resolved
+        };
+        if resolved.is_empty() && source.is_dynamic() {
+            return Err(format!("{source} lists no admin server"));
+        }
+        for url in resolved {
+            let url = url.trim_end_matches('/').to_string();
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+    }
+    Ok(urls)
+}
+
+/// Look up the SRV record `name`, ordered by priority then weight.
+fn resolve_srv(scheme: &str, name: &str) -> Result<Vec<String>, String> {
+    // The resolver drives its own runtime, which can't start on a thread
+    // already inside one, e.g. when called from an async `main`.
+    let name = name.to_string();
+    let lookup = std::thread::spawn(move || {
+        let resolver = Resolver::from_system_conf()
+            .map_err(|e| format!("failed to read the system DNS config: {e}"))?;
+        let records = resolver
+            .srv_lookup(name.as_str())
+            .map_err(|e| format!("SRV lookup of {name} failed: {e}"))?;
+        let mut records: Vec<(u16, u16, String, u16)> = records
+            .iter()
+            .map(|srv| {
+                let target = srv.target().to_utf8();
+                let target = target.trim_end_matches('.').to_string();
+                (srv.priority(), u16::MAX - srv.weight(), target, srv.port())
+            })
+            .collect();
+        records.sort();
+        Ok::<_, String>(records)
+    })
+    .join()
+    .map_err(|_| "SRV lookup panicked".to_string())??;
+    Ok(lookup
+        .into_iter()
+        .map(|(_, _, host, port)| format!("{scheme}://{host}:{port}"))
+        .collect())
+}
+
+/// Parse a JSON array of urls, or urls separated by commas or new lines.
+fn parse_list(contents: &str) -> Vec<String> {
+    if let Ok(Value::Array(urls)) = serde_json::from_str(contents) {
+        return urls
+            .iter()
+            .filter_map(Value::as_str)
+            .map(ToString::to_string)
+            .collect();
+    }
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .flat_map(|line| line.split(','))
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RegisterConfig;
+
+    #[test]
+    fn test_parse_source() {
+        assert_eq!(
+            ServerSource::parse("srv+https://_shenyu._tcp.example.com"),
+            ServerSource::Srv {
+                scheme: "https".to_string(),
+                name: "_shenyu._tcp.example.com".to_string(),
+            }
+        );
+        assert_eq!(
+            ServerSource::parse("file:///etc/shenyu/admins.txt"),
+            ServerSource::File("/etc/shenyu/admins.txt".to_string())
+        );
+        assert_eq!(
+            ServerSource::parse("list+http://config/admins"),
+            ServerSource::List("http://config/admins".to_string())
+        );
+        assert!(!ServerSource::parse("http://127.0.0.1:9095").is_dynamic());
+    }
+
+    #[test]
+    fn test_parse_list() {
+        assert_eq!(
+            parse_list("# admins\nhttp://a:9095, http://b:9095\n\nhttp://c:9095 # spare\n"),
+            ["http://a:9095", "http://b:9095", "http://c:9095"]
+        );
+        assert_eq!(
+            parse_list(r#"["http://a:9095", "http://b:9095"]"#),
+            ["http://a:9095", "http://b:9095"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_file() {
+        let path = std::env::temp_dir().join(format!("shenyu-admins-{}", std::process::id()));
+        std::fs::write(&path, "http://b:9095\nhttp://a:9095/\nftp://c\nnot a url\n").unwrap();
+        let transport = Transport::new(&RegisterConfig::default()).unwrap();
+        let servers = format!("http://a:9095,file://{}", path.display());
+        let urls = resolve(&servers, &transport);
+        std::fs::write(&path, "admin:9095").unwrap();
+        let empty = resolve(&servers, &transport);
+        _ = std::fs::remove_file(&path);
+        assert_eq!(urls.unwrap(), ["http://a:9095", "http://b:9095"]);
+        assert!(empty.is_err());
+    }
+}
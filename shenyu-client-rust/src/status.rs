@@ -0,0 +1,447 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! The registration state of a client, per admin target and namespace.
+//!
+//! Read it with [`crate::core::ShenyuClient::status`], e.g. from a health
+//! check, or serve it as JSON at [`STATUS_PATH`] with the axum or actix
+//! integration.
+
+use crate::core::{Call, Operation};
+use crate::error::ShenYuError;
+use crate::model::UriInfo;
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{PoisonError, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Where the registration with a target and namespace stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationState {
+    /// Nothing was registered yet, or every registration call failed.
+    Unregistered,
+    /// A registration is running.
+    Registering,
+    /// Every registration call and the latest heartbeats succeeded.
+    Registered,
+    /// Registered, but some admin server failed the latest calls.
+    Degraded,
+    /// The instance was taken offline.
+    Offline,
+}
+
+/// Where the status endpoint is usually mounted.
+pub const STATUS_PATH: &str = "/shenyu/status";
+
+/// A snapshot of the registration state of a client.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientStatus {
+    /// The app name.
+    pub app_name: String,
+    /// The context path of the app.
+    pub context_path: String,
+    /// The advertised host.
+    pub host: String,
+    /// The advertised port.
+    pub port: u16,
+    /// The routes registered.
+    pub uri_infos: Vec<UriInfo>,
+    /// The state per target and namespace, in configuration order.
+    pub namespaces: Vec<NamespaceStatus>,
+}
+
+impl ClientStatus {
+    /// Whether every target and namespace is [`RegistrationState::Registered`].
+    #[must_use]
+    pub fn is_registered(&self) -> bool {
+        self.namespaces
+            .iter()
+            .all(|namespace| namespace.state == RegistrationState::Registered)
+    }
+
+    /// The status of `namespace_id` in the target named `target`.
+    #[must_use]
+    pub fn namespace(&self, target: &str, namespace_id: &str) -> Option<&NamespaceStatus> {
+        self.namespaces
+            .iter()
+            .find(|namespace| namespace.target == target && namespace.namespace_id == namespace_id)
+    }
+}
+
+/// The registration state of a namespace of an admin target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamespaceStatus {
+    /// The name of the target.
+    pub target: String,
+    /// The admin namespace.
+    pub namespace_id: String,
+    /// Where the registration stands.
+    pub state: RegistrationState,
+    /// The latest error of a call to an admin server.
+    pub last_error: Option<String>,
+    /// When a call to an admin server last succeeded, as milliseconds since
+    /// the epoch in JSON.
+    #[serde(serialize_with = "epoch_millis")]
+    pub last_success: Option<SystemTime>,
+    /// The metadata status per route path.
+    pub routes: BTreeMap<String, RouteStatus>,
+}
+
+/// The metadata registration status of a route.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteStatus {
+    /// Whether every admin server accepted the latest metadata.
+    pub registered: bool,
+    /// The latest error registering the metadata.
+    pub last_error: Option<String>,
+}
+
+/// The tracked state of every target and namespace.
+#[derive(Debug, Default)]
+pub(crate) struct StatusBoard {
+    entries: RwLock<BTreeMap<(String, String), Entry>>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    state: RegistrationState,
+    last_error: Option<String>,
+    last_success: Option<SystemTime>,
+    routes: BTreeMap<String, RouteStatus>,
+    /// The operations or routes failing, with the admin server failing them.
+    failing: BTreeSet<(String, String)>,
+    /// Whether anything succeeded during the running registration.
+    registering_succeeded: bool,
+    ever_registered: bool,
+}
+
+impl Default for Entry {
+    fn default() -> Self {
+        Entry {
+            state: RegistrationState::Unregistered,
+            last_error: None,
+            last_success: None,
+            routes: BTreeMap::new(),
+            failing: BTreeSet::new(),
+            registering_succeeded: false,
+            ever_registered: false,
+        }
+    }
+}
+
+impl Entry {
+    /// The state once a registration, or a call outside one, settled.
+    fn settled(&self) -> RegistrationState {
+        if self.failing.is_empty() && (self.ever_registered || self.registering_succeeded) {
+            RegistrationState::Registered
+        } else if self.ever_registered || self.registering_succeeded {
+            RegistrationState::Degraded
+        } else {
+            RegistrationState::Unregistered
+        }
+    }
+
+    fn set_state(&mut self, state: RegistrationState) {
+        self.state = state;
+        if state == RegistrationState::Registered {
+            self.ever_registered = true;
+        }
+    }
+}
+
+impl StatusBoard {
+    /// A registration of `namespace_id` with `target` starts.
+    pub(crate) fn begin(&self, target: &str, namespace_id: &str) {
+        self.update(target, namespace_id, |entry| {
+            entry.state = RegistrationState::Registering;
+            entry.registering_succeeded = false;
+        });
+    }
+
+    /// The registration of `namespace_id` with `target` finished.
+    pub(crate) fn finish(&self, target: &str, namespace_id: &str) {
+        self.update(target, namespace_id, |entry| {
+            let state = entry.settled();
+            entry.set_state(state);
+        });
+    }
+
+    /// Record the result of `call` to the admin server `server`.
+    ///
+    /// Once offline, only another offline call is recorded until the next
+    /// [`StatusBoard::begin`].
+    pub(crate) fn record(
+        &self,
+        target: &str,
+        server: &str,
+        call: &Call<'_>,
+        result: Result<(), &ShenYuError>,
+    ) {
+        self.update(target, call.namespace, |entry| {
+            if entry.state == RegistrationState::Offline && call.operation != Operation::Offline {
+                return;
+            }
+            match result {
+                Ok(()) => entry.last_success = Some(SystemTime::now()),
+                Err(e) => entry.last_error = Some(e.to_string()),
+            }
+            if call.operation == Operation::Offline {
+                if result.is_ok() {
+                    entry.failing.clear();
+                    entry.ever_registered = false;
+                    entry.set_state(RegistrationState::Offline);
+                }
+                return;
+            }
+
+            // A heartbeat is the uri registration sent again.
+            let operation = match call.operation {
+                Operation::Heartbeat => Operation::RegisterUri,
+                operation => operation,
+            };
+            let key = (
+                call.route.unwrap_or(operation.as_str()).to_string(),
+                server.to_string(),
+            );
+            match result {
+                Ok(()) => _ = entry.failing.remove(&key),
+                Err(_) => _ = entry.failing.insert(key),
+            }
+            if let Some(route) = call.route {
+                let registered = !entry.failing.iter().any(|(failing, _)| failing == route);
+                let status = entry.routes.entry(route.to_string()).or_default();
+                status.registered = registered;
+                if let Err(e) = result {
+                    status.last_error = Some(e.to_string());
+                }
+            }
+            if entry.state == RegistrationState::Registering {
+                entry.registering_succeeded |= result.is_ok();
+            } else {
+                let state = entry.settled();
+                entry.set_state(state);
+            }
+        });
+    }
+
+    /// The status of `namespace_id` in `target`.
+    pub(crate) fn namespace(&self, target: &str, namespace_id: &str) -> NamespaceStatus {
+        let entries = self.entries.read().unwrap_or_else(PoisonError::into_inner);
+        let key = (target.to_string(), namespace_id.to_string());
+        let default = Entry::default();
+        let entry = entries.get(&key).unwrap_or(&default);
+        NamespaceStatus {
+            target: target.to_string(),
+            namespace_id: namespace_id.to_string(),
+            state: entry.state,
+            last_error: entry.last_error.clone(),
+            last_success: entry.last_success,
+            routes: entry.routes.clone(),
+        }
+    }
+
+    /// The routes of `namespace_id` in `target` whose latest metadata the
+    /// admin server `server` did not fail, none once offline.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn registered_routes(
+        &self,
+        target: &str,
+        server: &str,
+        namespace_id: &str,
+    ) -> usize {
+        let entries = self.entries.read().unwrap_or_else(PoisonError::into_inner);
+        let key = (target.to_string(), namespace_id.to_string());
+        let Some(entry) = entries.get(&key) else {
+            return 0;
+        };
+        if entry.state == RegistrationState::Offline {
+            return 0;
+        }
+        entry
+            .routes
+            .keys()
+            .filter(|route| {
+                !entry
+                    .failing
+                    .contains(&((*route).clone(), server.to_string()))
+            })
+            .count()
+    }
+
+    fn update<F: FnOnce(&mut Entry)>(&self, target: &str, namespace_id: &str, f: F) {
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        f(entries
+            .entry((target.to_string(), namespace_id.to_string()))
+            .or_default());
+    }
+}
+
+/// The status code and JSON body answering a status request with the
+/// `Authorization` header `authorization`, when `token` is required.
+#[cfg(any(feature = "axum", feature = "actix-web"))]
+pub(crate) fn respond(
+    client: &crate::core::ShenyuClient,
+    token: Option<&crate::secret::Secret>,
+    authorization: Option<&str>,
+) -> (u16, String) {
+    if let Some(token) = token {
+        let bearer = authorization.and_then(|value| value.strip_prefix("Bearer "));
+        if !bearer.is_some_and(|bearer| constant_time_eq(bearer, token.expose())) {
+            return (401, r#"{"error":"unauthorized"}"#.to_string());
+        }
+    }
+    match serde_json::to_string(&client.status()) {
+        Ok(json) => (200, json),
+        Err(e) => (
+            500,
+            serde_json::json!({ "error": e.to_string() }).to_string(),
+        ),
+    }
+}
+
+/// Compare without returning early, so the time taken doesn't tell how
+/// much of the token was guessed.
+#[cfg(any(feature = "axum", feature = "actix-web"))]
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[allow(clippy::ref_option)]
+fn epoch_millis<S: Serializer>(
+    time: &Option<SystemTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let millis = time
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|elapsed| u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX));
+    millis.serialize(serializer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(operation: Operation, route: Option<&str>) -> Call<'_> {
+        Call {
+            operation,
+            namespace: "dev",
+            route,
+        }
+    }
+
+    fn state(board: &StatusBoard) -> RegistrationState {
+        board.namespace("default", "dev").state
+    }
+
+    #[test]
+    fn test_registration() {
+        let board = StatusBoard::default();
+        let error = ShenYuError::transport("connect", "refused");
+        assert_eq!(state(&board), RegistrationState::Unregistered);
+
+        board.begin("default", "dev");
+        assert_eq!(state(&board), RegistrationState::Registering);
+        let route = call(Operation::RegisterMetadata, Some("/health"));
+        board.record("default", "http://a", &route, Err(&error));
+        board.finish("default", "dev");
+        assert_eq!(state(&board), RegistrationState::Unregistered);
+
+        board.begin("default", "dev");
+        board.record("default", "http://a", &route, Ok(()));
+        let uri = call(Operation::RegisterUri, None);
+        board.record("default", "http://a", &uri, Ok(()));
+        board.finish("default", "dev");
+        let status = board.namespace("default", "dev");
+        assert_eq!(status.state, RegistrationState::Registered);
+        assert!(status.last_success.is_some());
+        assert_eq!(
+            status.routes["/health"],
+            RouteStatus {
+                registered: true,
+                last_error: Some(error.to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_heartbeat_and_offline() {
+        let board = StatusBoard::default();
+        let error = ShenYuError::transport("connect", "refused");
+        board.begin("default", "dev");
+        board.record(
+            "default",
+            "http://a",
+            &call(Operation::RegisterUri, None),
+            Ok(()),
+        );
+        board.finish("default", "dev");
+
+        let heartbeat = call(Operation::Heartbeat, None);
+        board.record("default", "http://a", &heartbeat, Err(&error));
+        assert_eq!(state(&board), RegistrationState::Degraded);
+        board.record("default", "http://a", &heartbeat, Ok(()));
+        assert_eq!(state(&board), RegistrationState::Registered);
+
+        let offline = call(Operation::Offline, None);
+        board.record("default", "http://a", &offline, Err(&error));
+        assert_eq!(state(&board), RegistrationState::Registered);
+        board.record("default", "http://a", &offline, Ok(()));
+        assert_eq!(state(&board), RegistrationState::Offline);
+
+        board.record("default", "http://a", &heartbeat, Ok(()));
+        assert_eq!(state(&board), RegistrationState::Offline);
+        board.record("default", "http://a", &heartbeat, Err(&error));
+        assert_eq!(state(&board), RegistrationState::Offline);
+
+        board.begin("default", "dev");
+        board.finish("default", "dev");
+        assert_eq!(state(&board), RegistrationState::Unregistered);
+    }
+
+    #[test]
+    #[cfg(feature = "metrics")]
+    fn test_registered_routes() {
+        let board = StatusBoard::default();
+        let error = ShenYuError::transport("connect", "refused");
+        let users = call(Operation::RegisterMetadata, Some("/users"));
+        let orders = call(Operation::RegisterMetadata, Some("/orders"));
+        board.begin("default", "dev");
+        for server in ["http://a", "http://b"] {
+            board.record("default", server, &users, Ok(()));
+        }
+        board.record("default", "http://a", &orders, Ok(()));
+        board.record("default", "http://b", &orders, Err(&error));
+        board.finish("default", "dev");
+        assert_eq!(board.registered_routes("default", "http://a", "dev"), 2);
+        assert_eq!(board.registered_routes("default", "http://b", "dev"), 1);
+        assert_eq!(board.registered_routes("default", "http://a", "prod"), 0);
+
+        board.record("default", "http://b", &orders, Ok(()));
+        assert_eq!(board.registered_routes("default", "http://b", "dev"), 2);
+        let offline = call(Operation::Offline, None);
+        board.record("default", "http://a", &offline, Ok(()));
+        assert_eq!(board.registered_routes("default", "http://a", "dev"), 0);
+    }
+}
@@ -0,0 +1,126 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Shenyu admin releases and what they support.
+//!
+//! The release of a target is set with `register.admin_version`, or
+//! detected per admin server after logging in: an admin answering 404 at
+//! [`crate::core::NAMESPACE_LIST_SUFFIX`] predates namespaces, and one also
+//! answering 404 at [`crate::core::REGISTER_DISCOVERY_CONFIG_SUFFIX`]
+//! predates discovery configs.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// A shenyu admin release, as `major.minor.patch`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AdminVersion {
+    /// The major version.
+    pub major: u32,
+    /// The minor version.
+    pub minor: u32,
+    /// The patch version.
+    pub patch: u32,
+}
+
+impl AdminVersion {
+    /// The first release with namespaces, assumed when the release is
+    /// unknown.
+    pub const NAMESPACES: AdminVersion = AdminVersion::new(2, 7, 0);
+
+    /// The first release registering discovery configs.
+    pub const DISCOVERY_CONFIG: AdminVersion = AdminVersion::new(2, 6, 0);
+
+    /// The release detected for admins without either.
+    pub const LEGACY: AdminVersion = AdminVersion::new(2, 5, 0);
+
+    /// The release `major.minor.patch`.
+    #[must_use]
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        AdminVersion {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Whether payloads carry a `namespaceId`. Older admins are sent each
+    /// registration once, without it.
+    #[must_use]
+    pub fn supports_namespaces(self) -> bool {
+        self >= Self::NAMESPACES
+    }
+
+    /// Whether the discovery config is registered.
+    #[must_use]
+    pub fn supports_discovery_config(self) -> bool {
+        self >= Self::DISCOVERY_CONFIG
+    }
+}
+
+impl Display for AdminVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for AdminVersion {
+    type Err = String;
+
+    /// Parse `2.6.1`, `v2.6` or `2.7.0-SNAPSHOT`, the patch defaulting to 0.
+    fn from_str(version: &str) -> Result<Self, Self::Err> {
+        let release = version
+            .trim()
+            .trim_start_matches('v')
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default();
+        let numbers = release
+            .split('.')
+            .map(str::parse)
+            .collect::<Result<Vec<u32>, _>>()
+            .map_err(|_| format!("`{version}` is not a version like `2.6.1`"))?;
+        match numbers[..] {
+            [major, minor] => Ok(AdminVersion::new(major, minor, 0)),
+            [major, minor, patch] => Ok(AdminVersion::new(major, minor, patch)),
+            _ => Err(format!("`{version}` is not a version like `2.6.1`")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("2.6.1".parse(), Ok(AdminVersion::new(2, 6, 1)));
+        assert_eq!("v2.7".parse(), Ok(AdminVersion::new(2, 7, 0)));
+        assert_eq!("2.7.0-SNAPSHOT".parse(), Ok(AdminVersion::NAMESPACES));
+        assert!("2".parse::<AdminVersion>().is_err());
+        assert!("latest".parse::<AdminVersion>().is_err());
+    }
+
+    #[test]
+    fn test_supports() {
+        let version = AdminVersion::new(2, 6, 1);
+        assert!(!version.supports_namespaces());
+        assert!(version.supports_discovery_config());
+        assert!(!AdminVersion::LEGACY.supports_discovery_config());
+        assert!(AdminVersion::new(2, 7, 1).supports_namespaces());
+    }
+}
@@ -0,0 +1,274 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use crate::config::ShenYuConfig;
+use crate::core::ShenyuClient;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Watches a config file and reloads the client when it changes.
+///
+/// The file is polled every `interval`. When its contents change it is
+/// loaded again with [`ShenYuConfig::from_path`] and applied
+/// with [`ShenyuClient::reload`]. A file that fails to load or validate is
+/// logged and the running configuration is kept.
+///
+/// The watcher stops when dropped.
+///
+/// # Examples
+/// ```rust,no_run
+/// use shenyu_client_rust::config::ShenYuConfig;
+/// use shenyu_client_rust::core::ShenyuClient;
+/// use shenyu_client_rust::watch::ConfigWatcher;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// let config = ShenYuConfig::from_yaml_file("config.yml").unwrap();
+/// let client = Arc::new(ShenyuClient::new(config, "app", &[], 3000).unwrap());
+/// client.register().unwrap();
+/// let _watcher = ConfigWatcher::spawn(client.clone(), "config.yml", Duration::from_secs(5));
+/// ```
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    stopped: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ConfigWatcher {
+    /// Start watching `path` on a background thread.
+    pub fn spawn<P: Into<PathBuf>>(client: Arc<ShenyuClient>, path: P, interval: Duration) -> Self {
+        let path = path.into();
+        // Taken before spawning, so a change made right after `spawn`
+        // returns isn't mistaken for the initial state.
+        let mut last = fingerprint(&path);
+        let (stopped, handle) = poll("shenyu-config-watcher", interval, move || {
+            let current = fingerprint(&path);
+            if current != last {
+                last = current;
+                reload(&client, &path);
+            }
+        });
+        ConfigWatcher { stopped, handle }
+    }
+
+    /// Stop watching and wait for the background thread to exit.
+    pub fn stop(mut self) {
+        shutdown(&self.stopped, &mut self.handle);
+    }
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        shutdown(&self.stopped, &mut self.handle);
+    }
+}
+
+/// Re-resolves the admin servers of the client periodically.
+///
+/// Every `interval`, [`ShenyuClient::refresh_servers`] resolves the
+/// `servers` entries listing DNS SRV names, files or urls again, and
+/// registers with the admin servers that appeared.
+///
+/// The refresher stops when dropped.
+#[derive(Debug)]
+pub struct ServerRefresher {
+    stopped: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ServerRefresher {
+    /// Start refreshing on a background thread.
+    pub fn spawn(client: Arc<ShenyuClient>, interval: Duration) -> Self {
+        let (stopped, handle) = poll("shenyu-server-refresher", interval, move || {
+            let report = client.refresh_servers();
+            for target in report.targets.iter().filter(|target| !target.is_success()) {
+                warn!(
+                    target = %target.name,
+                    failures = ?target.failures,
+                    "registration with new admin servers failed"
+                );
+            }
+        });
+        ServerRefresher { stopped, handle }
+    }
+
+    /// Stop refreshing and wait for the background thread to exit.
+    pub fn stop(mut self) {
+        shutdown(&self.stopped, &mut self.handle);
+    }
+}
+
+impl Drop for ServerRefresher {
+    fn drop(&mut self) {
+        shutdown(&self.stopped, &mut self.handle);
+    }
+}
+
+/// Sends the heartbeat of the client periodically.
+///
+/// Every `interval`, [`ShenyuClient::heartbeat`] sends the uri registration
+/// again to every admin server, unless the client went offline.
+///
+/// The heartbeat stops when dropped.
+#[derive(Debug)]
+pub struct Heartbeat {
+    stopped: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Heartbeat {
+    /// Start sending heartbeats on a background thread.
+    pub fn spawn(client: Arc<ShenyuClient>, interval: Duration) -> Self {
+        let (stopped, handle) = poll("shenyu-heartbeat", interval, move || {
+            let report = client.heartbeat();
+            for target in report.targets.iter().filter(|target| !target.is_success()) {
+                warn!(target = %target.name, failures = ?target.failures, "heartbeat failed");
+            }
+        });
+        Heartbeat { stopped, handle }
+    }
+
+    /// Stop sending heartbeats and wait for the background thread to exit.
+    pub fn stop(mut self) {
+        shutdown(&self.stopped, &mut self.handle);
+    }
+}
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        shutdown(&self.stopped, &mut self.handle);
+    }
+}
+
+/// Run `tick` every `interval` on a background thread, until the returned
+/// flag is set.
+fn poll<F>(name: &str, interval: Duration, mut tick: F) -> (Arc<AtomicBool>, Option<JoinHandle<()>>)
+where
+    F: FnMut() + Send + 'static,
+{
+    let stopped = Arc::new(AtomicBool::new(false));
+    let thread_stopped = stopped.clone();
+    let handle = std::thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || loop {
+            std::thread::park_timeout(interval);
+            if thread_stopped.load(Ordering::Acquire) {
+                break;
+            }
+            tick();
+        })
+        .ok();
+    if handle.is_none() {
+        warn!("Failed to start the {name} thread");
+    }
+    (stopped, handle)
+}
+
+fn shutdown(stopped: &AtomicBool, handle: &mut Option<JoinHandle<()>>) {
+    stopped.store(true, Ordering::Release);
+    if let Some(handle) = handle.take() {
+        handle.thread().unpark();
+        _ = handle.join();
+    }
+}
+
+/// A hash of the contents of `path`, which unlike its modification time
+/// and size tells apart quick edits keeping the size.
+fn fingerprint(path: &Path) -> Option<u64> {
+    let contents = std::fs::read(path).ok()?;
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+fn reload(client: &ShenyuClient, path: &Path) {
+    let config = match ShenYuConfig::from_path(path) {
+        Ok(config) => config,
+        Err(e) => {
+            warn!(
+                "[RELOAD], failed to load {}, keeping the current config: {e}",
+                path.display()
+            );
+            return;
+        }
+    };
+    match client.reload(config) {
+        Ok(diff) if diff.changed => info!("[RELOAD], applied {}", path.display()),
+        Ok(_) => {}
+        Err(e) => warn!(
+            "[RELOAD], rejected {}, keeping the current config: {e}",
+            path.display()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::HostMode;
+
+    #[test]
+    fn test_reload_on_change() {
+        let path = std::env::temp_dir().join(format!("shenyu-watch-{}.yml", std::process::id()));
+        let config = ShenYuConfig::builder()
+            .servers("http://127.0.0.1:9095")
+            .namespace_id("dev")
+            .register_prop("username", "admin")
+            .register_prop("password", "123456")
+            .host("10.0.0.1")
+            .host_mode(HostMode::Literal)
+            .context_path("/orders")
+            .build();
+        std::fs::write(&path, config.to_yaml_string().unwrap()).unwrap();
+        let client = Arc::new(ShenyuClient::new(config.clone(), "app", &[], 3000).unwrap());
+        let watcher = ConfigWatcher::spawn(client.clone(), &path, Duration::from_millis(20));
+
+        let mut updated = config;
+        updated.uri.context_path = "/orders-v2".to_string();
+        std::fs::write(&path, updated.to_yaml_string().unwrap()).unwrap();
+        for _ in 0..100 {
+            if client.config().uri.context_path == "/orders-v2" {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        watcher.stop();
+        _ = std::fs::remove_file(&path);
+        assert_eq!(client.config().uri.context_path, "/orders-v2");
+    }
+
+    #[test]
+    fn test_fingerprint_follows_contents() {
+        let path = std::env::temp_dir().join(format!("shenyu-print-{}.yml", std::process::id()));
+        std::fs::write(&path, "context_path: /orders-v1").unwrap();
+        let first = fingerprint(&path);
+        std::fs::write(&path, "context_path: /orders-v2").unwrap();
+        let edited = fingerprint(&path);
+        std::fs::write(&path, "context_path: /orders-v2").unwrap();
+        let rewritten = fingerprint(&path);
+        _ = std::fs::remove_file(&path);
+        assert!(first.is_some());
+        assert_ne!(first, edited);
+        assert_eq!(edited, rewritten);
+    }
+}